@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use advent_of_code_2021::day1;
+use advent_of_code_2021::utils::input::Input;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day1::day1(&Input::new(text.to_string()));
+    }
+});
@@ -1,8 +1,15 @@
 use itertools::Itertools;
 
-pub fn day1(input_lines: &[String]) -> (u64, u64) {
-    let depths: Vec<u64> = input_lines.iter().map(|line| line.parse::<u64>().expect("Failed to parse input")).collect();
+use crate::utils::answer::Answer;
+use crate::utils::input::Input;
+use crate::utils::parse::ParseResult;
+
+/// Three depths are summed at once, so each has to fit three times over.
+const MAX_DEPTH: u64 = u64::MAX / 3;
+
+pub fn day1(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let depths: Vec<u64> = input.lines().parse_each(|line| line.as_token().parse_in(0..=MAX_DEPTH))?;
     let part1 = depths.iter().tuple_windows().filter(|(first, second)| second > first).count() as u64;
     let part2 = depths.iter().tuple_windows::<(_, _, _)>().map(|(first, second, third)| first + second + third).tuple_windows().filter(|(first, second)| second > first).count() as u64;
-    (part1, part2) 
+    Ok((part1.into(), part2.into()))
 }
\ No newline at end of file
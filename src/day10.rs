@@ -1,14 +1,21 @@
 use std::collections::HashMap;
 
-pub fn day10(input_lines: &[String]) -> (u64, u64) {
+use num_bigint::BigUint;
+
+use crate::utils::answer::Answer;
+use crate::utils::input::Input;
+use crate::utils::parse::ParseResult;
+
+pub fn day10(input: &Input) -> ParseResult<(Answer, Answer)> {
     let illegal_scores: HashMap<char, u64> = HashMap::from([(')', 3), (']', 57), ('}', 1197), ('>', 25137)]);
 
     let mut illegal_score = 0u64;
-    let mut incomplete_scores: Vec<u64> = Vec::with_capacity(input_lines.len());
-    for line in input_lines {
-        let mut chunks: Vec<char> = Vec::with_capacity(line.len() / 2);
+    // A long enough run of unclosed chunks scores more than a u64 can hold.
+    let mut incomplete_scores: Vec<BigUint> = Vec::with_capacity(input.len());
+    for line in input.lines().iter() {
+        let mut chunks: Vec<char> = Vec::with_capacity(line.text().len() / 2);
         let mut legal = true;
-        for c in line.chars() {
+        for (index, c) in line.text().chars().enumerate() {
             let current_chunk = chunks.last();
             match c {
                 '(' => chunks.push(')'),
@@ -24,16 +31,15 @@ pub fn day10(input_lines: &[String]) -> (u64, u64) {
                         break;
                     }
                 },
-                _ => unreachable!("Invalid character in input"),
+                _ => return Err(line.error(index, format!("expected a bracket, found {:?}", c))),
             }
         }
 
         if legal && !chunks.is_empty() {
-            let mut incomplete_score = 0u64;
+            let mut incomplete_score = BigUint::default();
             while let Some(chunk) = chunks.pop() {
-                incomplete_score *= 5;
-                incomplete_score += match chunk {
-                    ')' => 1,
+                incomplete_score = incomplete_score * 5u32 + match chunk {
+                    ')' => 1u32,
                     ']' => 2,
                     '}' => 3,
                     '>' => 4,
@@ -44,8 +50,11 @@ pub fn day10(input_lines: &[String]) -> (u64, u64) {
         }
     }
     let part1 = illegal_score;
+    if incomplete_scores.is_empty() {
+        return Err(input.lines().end_error("no lines are incomplete".to_string()));
+    }
     incomplete_scores.sort_unstable();
-    let part2 = incomplete_scores[incomplete_scores.len() / 2];
+    let part2 = incomplete_scores.swap_remove(incomplete_scores.len() / 2);
 
-    (part1,part2)
+    Ok((part1.into(), part2.into()))
 }
\ No newline at end of file
@@ -1,58 +1,75 @@
-use itertools::Itertools;
+use crate::utils::answer::Answer;
+use crate::utils::cycle::{self, Cycle};
+use crate::utils::grid::{Grid, Position};
+use crate::utils::input::Input;
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::{ParseError, ParseResult};
+use crate::utils::visualize;
 
-const GRID_SIZE: usize = 10;
-const I_GRID_SIZE: isize = GRID_SIZE as isize;
-const NUM_OCTOPUSES: usize = GRID_SIZE * GRID_SIZE;
+static GRID_SIZE: Param<usize> = Param::new("grid_size", "width and height of the grid of octopuses", 10, 1);
+static PART1_STEPS: Param<usize> = Param::new("part1_steps", "steps to count flashes over for part 1", 100, 1);
+pub static PARAMS: [&dyn Parameter; 2] = [&GRID_SIZE, &PART1_STEPS];
 
-pub fn day11(input_lines: &[String]) -> (u64, u64) {
-    let mut octopuses = [[Octopus::new(0); GRID_SIZE]; GRID_SIZE];
-    for (row, line) in input_lines.iter().enumerate() {
-        for (col, c) in line.chars().enumerate() {
-            octopuses[row][col] = Octopus::new(c.to_digit(10).expect("Invalid character in input"));
-        }
-    }
+pub fn day11(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let grid_size = GRID_SIZE.get();
+    let part1_steps = PART1_STEPS.get() as u64;
+    let octopuses = parse_input(input, grid_size)?;
 
-    let mut num_flashes = 0usize;
-    let mut num_steps = 0u64;
-    let mut part1 = 0u64;
-    loop {
-        num_steps += 1;
-        let mut num_flashes_this_step = 0usize;
+    // There are only so many states the octopuses can be in, so they always
+    // end up repeating themselves. Everything we want to know happens
+    // before then, or is a repeat of something that did.
+    let history = cycle::history(octopuses, |octopuses| {
+        let mut octopuses = octopuses.clone();
+        step(&mut octopuses);
+        octopuses
+    });
 
-        for (row, col) in (0..GRID_SIZE).cartesian_product(0..GRID_SIZE) {
-            let mut flashes: Vec<(isize, isize)> = Vec::new();
-            if octopuses[row][col].increment() {
-                num_flashes_this_step += 1;
-                flashes.push((row as isize, col as isize));
-                while let Some((flash_row, flash_col)) = flashes.pop() {
-                    for (inc_row, inc_col) in (flash_row-1..=flash_row+1).cartesian_product(flash_col-1..=flash_col+1) {
-                        if (0..I_GRID_SIZE).contains(&inc_row) && (0..I_GRID_SIZE).contains(&inc_col) && octopuses[inc_row as usize][inc_col as usize].increment() {
-                            num_flashes_this_step += 1;
-                            flashes.push((inc_row, inc_col));
-                        }
+    // Exactly the octopuses that flashed during a step end it with no energy.
+    let flashes = |octopuses: &Grid<Octopus>| octopuses.iter().filter(|octopus| octopus.energy == 0).count() as u64;
+    let part1 = history.sum_over(part1_steps, flashes);
+    // The last step of the first time round the cycle gets back to the state
+    // it started from, which might be the initial state, so it's looked at too.
+    let Cycle { start, length } = history.cycle;
+    let part2 = match (1..=(start + length) as u64).find(|&step| flashes(history.state_at(step)) == (grid_size * grid_size) as u64) {
+        Some(step) => step,
+        None => return Err(input.lines().end_error(format!("octopuses start repeating after {} steps without all flashing at once", start + length))),
+    };
+
+    Ok((part1.into(), part2.into()))
+}
+
+fn step(octopuses: &mut Grid<Octopus>) {
+    for position in octopuses.positions() {
+        let mut flashes: Vec<Position> = Vec::new();
+        if octopuses[position].increment() {
+            flashes.push(position);
+            while let Some(flash) = flashes.pop() {
+                let neighbours: Vec<Position> = octopuses.neighbours8(flash).collect();
+                for neighbour in neighbours {
+                    if octopuses[neighbour].increment() {
+                        flashes.push(neighbour);
                     }
                 }
             }
         }
+    }
 
-        num_flashes += num_flashes_this_step;
-        if num_steps == 100 {
-            part1 = num_flashes as u64;
-        }
-        if num_flashes_this_step == NUM_OCTOPUSES {
-            break;
-        }
+    if visualize::enabled() {
+        visualize::push(visualize::Frame::from_grid(octopuses, Octopus::colour));
+    }
+    octopuses.iter_mut().for_each(Octopus::reset);
+}
 
-        for (row, col) in (0..GRID_SIZE).cartesian_product(0..GRID_SIZE) {
-            octopuses[row][col].reset();
-        }
+fn parse_input(input: &Input, grid_size: usize) -> ParseResult<Grid<Octopus>> {
+    let energies: Grid<u32> = input.lines().digit_grid()?;
+    if energies.width() != grid_size || energies.height() != grid_size {
+        return Err(ParseError { line: 1, column: 1, message: format!("expected a {}x{} grid", grid_size, grid_size) });
     }
-    let part2 = num_steps;
 
-    (part1,part2)
+    Ok(energies.map(|&energy| Octopus::new(energy)))
 }
 
-#[derive(Copy,Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 struct Octopus {
     energy: u32,
     flashed: bool,
@@ -84,4 +101,8 @@ impl Octopus {
     fn reset(&mut self) {
         self.flashed = false;
     }
+
+    fn colour(&self) -> visualize::Colour {
+        if self.flashed { visualize::YELLOW } else { visualize::shade(self.energy as u64, 9) }
+    }
 }
\ No newline at end of file
@@ -1,119 +1,112 @@
-use std::collections::HashMap;
+use crate::utils::answer::Answer;
+use crate::utils::graph::{self, Graph, NodeId};
+use crate::utils::input::Input;
+use crate::utils::memo::Memo;
+use crate::utils::parse::ParseResult;
+use crate::utils::small_set::SmallSet;
 
-pub fn day12(input_lines: &[String]) -> (u64, u64) {
-    let caves = parse_input(input_lines);
-    let part1 = explore(&caves, false);
-    let part2 = explore(&caves, true);
-    (part1,part2)
+pub fn day12(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let caves = parse_input(input)?;
+    if graph::enabled() {
+        graph::save("day12", &caves.to_dot(|cave| if cave.small { String::new() } else { "shape=box".to_string() }));
+    }
+    let start = caves.id("start").ok_or_else(|| input.lines().end_error("there's no start cave".to_string()))?;
+    let part1 = explore(&caves, start, false);
+    let part2 = explore(&caves, start, true);
+    Ok((part1.into(), part2.into()))
 }
 
-fn parse_input(input_lines: &[String]) -> Vec<Cave> {
-    let mut cave_dict: HashMap<&str, usize> = HashMap::with_capacity(input_lines.len());
-    let mut caves: Vec<Cave> = Vec::with_capacity(input_lines.len());
-
-    caves.push(Cave::new("start", 0));
-    cave_dict.insert("start", 0);
-    let mut cave_id = 1usize;
+fn parse_input(input: &Input) -> ParseResult<Graph<'_, Cave>> {
+    let mut caves: Graph<Cave> = Graph::new();
 
-    for line in input_lines {
-        let connected_caves = line.split('-').collect::<Vec<_>>();
-        let cave1_id = *cave_dict.entry(connected_caves[0]).or_insert(cave_id);
-        if cave1_id == cave_id {
-            let cave = Cave::new(connected_caves[0], cave_id);
-            caves.push(cave);
-            cave_id += 1;
-        }
-        let cave2_id = *cave_dict.entry(connected_caves[1]).or_insert(cave_id);
-        if cave2_id == cave_id {
-            let cave = Cave::new(connected_caves[1], cave_id);
-            caves.push(cave);
-            cave_id += 1;
-        }
-        if cave2_id != 0 {
-            caves[cave1_id].connections.push(cave2_id);
+    for line in input.lines().iter() {
+        let (cave1, cave2) = line.as_token().split_once("-")?;
+        for cave in [cave1, cave2] {
+            if cave.text().is_empty() || !cave.text().chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(cave.error(format!("expected a cave name, found {:?}", cave.text())));
+            }
         }
-        if cave1_id != 0 {
-            caves[cave2_id].connections.push(cave1_id);
+        if cave1.text().starts_with(|c: char| c.is_ascii_uppercase()) && cave2.text().starts_with(|c: char| c.is_ascii_uppercase()) {
+            // We'd be able to bounce between these two forever.
+            return Err(line.error(0, "two big caves can't be connected".to_string()));
         }
+        let cave1_id = caves.intern(cave1.text(), Cave::new);
+        let cave2_id = caves.intern(cave2.text(), Cave::new);
+        caves.connect(cave1_id, cave2_id);
     }
 
-    caves
+    Ok(caves)
 }
 
-fn explore(caves: &[Cave], allow_duplicate_small_cave: bool) -> u64 {
-    let mut num_paths = 0u64;
-    // Cave 0 is always the start
-    let mut paths_in_progress: Vec<Path> = vec![Path::new()];
+fn explore(caves: &Graph<Cave>, start: NodeId, allow_duplicate_small_cave: bool) -> u64 {
+    // Paths that have reached the same cave having visited the same small
+    // caves can finish in the same ways, so we only count those once.
+    let mut memo = Memo::new(if allow_duplicate_small_cave { "paths with a duplicate" } else { "paths" });
+    count_paths(caves, start, allow_duplicate_small_cave, &mut memo, Path::new(start))
+}
 
-    while let Some(path) = paths_in_progress.pop() {
-        let cave = &caves[path.current_position];
-        if cave.end {
-            num_paths += 1;
-        } else {
-            for connection in cave.connections.iter() {
-                if let Some(new_path) = Path::add(&path, &caves[*connection], allow_duplicate_small_cave) {
-                    paths_in_progress.push(new_path);
-                }
-            }
+/// How many ways there are to finish `path`.
+fn count_paths(caves: &Graph<Cave>, start: NodeId, allow_duplicate_small_cave: bool, memo: &mut Memo<Path, Path, u64>, path: Path) -> u64 {
+    memo.get(path, |memo, path| {
+        if caves.attributes(path.current_position).end {
+            return 1;
         }
-    }
-
-    num_paths
+        caves.neighbours(path.current_position).iter()
+            // You can't return to the start.
+            .filter(|&&connection| connection != start)
+            .filter_map(|&connection| Path::add(&path, connection, caves.attributes(connection), allow_duplicate_small_cave))
+            .map(|new_path| count_paths(caves, start, allow_duplicate_small_cave, memo, new_path))
+            .sum()
+    })
 }
 
 struct Cave {
-    id: usize,
     small: bool,
     end: bool,
-    connections: Vec<usize>,
 }
 
 impl Cave {
-    fn new(name: &str, id: usize) -> Self {
+    fn new(name: &str) -> Self {
         let small = name.chars().next().unwrap().is_lowercase();
         let end = name == "end";
-        Self {
-            id,
-            small,
-            end,
-            connections: Vec::new(),
-        }
+        Self { small, end }
     }
 }
 
-#[derive(Clone)]
+/// Only `Clone`, since a `SmallSet` isn't `Copy`: it has to grow onto the heap
+/// for cave systems with more caves than an `InlineSet` can hold.
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct Path {
-    current_position: usize,
-    small_caves_visited: usize,
+    current_position: NodeId,
+    small_caves_visited: SmallSet,
     duplicate_small_cave: bool,
 }
 
 impl Path {
-    fn new() -> Self {
+    fn new(start: NodeId) -> Self {
         Self {
-            // We ensure elsewhere that "start" is cave ID 0.
-            current_position: 0,
+            current_position: start,
             // You can't return to the start, so we don't need to record that you've visited it.
-            small_caves_visited: 0,
+            small_caves_visited: SmallSet::new(),
             duplicate_small_cave: false,
         }
     }
 
-    fn add(previous: &Self, next_step: &Cave, allow_duplicate_small_cave: bool) -> Option<Self> {
+    fn add(previous: &Self, next_id: NodeId, next_step: &Cave, allow_duplicate_small_cave: bool) -> Option<Self> {
         let cave_is_small = next_step.small;
-        let visited_small_cave_before = cave_is_small && (previous.small_caves_visited & (1 << next_step.id) != 0);
+        let visited_small_cave_before = cave_is_small && previous.small_caves_visited.contains(next_id);
         if visited_small_cave_before && (!allow_duplicate_small_cave || previous.duplicate_small_cave) {
             // This path has already visited this small cave, and either we're not
             // allowed to visit one twice, or we've already visited one twice.
             None
         } else {
             let mut new_path = previous.clone();
-            new_path.current_position = next_step.id;
+            new_path.current_position = next_id;
             if cave_is_small {
                 if visited_small_cave_before {
                     new_path.duplicate_small_cave = true;
                 } else {
-                    new_path.small_caves_visited |= 1 << next_step.id;
+                    new_path.small_caves_visited.insert(next_id);
                 }
             }
             Some(new_path)
@@ -1,30 +1,61 @@
 use itertools::Itertools;
-use std::{collections::HashSet, iter::FromIterator};
-use crate::utils;
+use std::collections::HashSet;
+use crate::utils::answer::Answer;
+use crate::utils::audit;
+use crate::utils::input::Input;
+use crate::utils::parse::{Lines, ParseResult};
+use crate::utils::vector::Vec2;
+use crate::utils::{ocr, svg, visualize};
 
-pub fn day13(input_lines: &[String]) -> (u64, u64) {
-    let (dots_input, folds_input) = utils::group_lines_split_by_empty_line(input_lines).into_iter().next_tuple().expect("Invalid input");
-    let mut dots = parse_dots(dots_input);
-    let folds = parse_folds(folds_input);
+type Dots = HashSet<Vec2>;
+type Fold = (char, i64);
+
+// Puzzle paper is about 1300 dots across, and anything up to this keeps the
+// folds' arithmetic and the frames drawn of the paper well within range.
+const MAX_COORDINATE: i64 = 1_000_000;
+
+// The code is eight letters, each six dots high and about five wide, so
+// paper folded down to much more than that isn't worth printing.
+const MAX_PRINTED_AREA: i64 = 10_000;
+
+pub fn day13(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let (mut dots, folds) = parse_input(input)?;
+    let mut stages: Vec<Dots> = Vec::new();
+    record_stage(&mut stages, &dots);
 
     perform_fold(&mut dots, folds[0]);
+    record_stage(&mut stages, &dots);
     let part1 = dots.len() as u64;
 
-    folds[1..].iter().for_each(|&fold| perform_fold(&mut dots, fold));
-    display_dots(&dots);
+    for &fold in folds[1..].iter() {
+        perform_fold(&mut dots, fold);
+        record_stage(&mut stages, &dots);
+    }
+    if svg::enabled() {
+        export_svg(&stages, &folds);
+    }
+    // If the letters can't be read, a person can still read the paper.
+    let part2 = ocr::read_points(dots.iter().copied()).unwrap_or_else(|glyph| {
+        display_dots(&dots);
+        format!("unreadable ({})", glyph)
+    });
 
-    (part1,0)
+    Ok((part1.into(), part2.into()))
 }
 
-fn display_dots(dots: &HashSet<(usize, usize)>) {
-    let cols = dots.iter().map(|(x, _)| *x).max().unwrap() + 1;
-    let rows = dots.iter().map(|(_, y)| *y).max().unwrap() + 1;
-    let mut display: Vec<Vec<char>> = Vec::with_capacity(rows);
+fn display_dots(dots: &Dots) {
+    let cols = dots.iter().map(|dot| dot.x).max().unwrap_or(0) + 1;
+    let rows = dots.iter().map(|dot| dot.y).max().unwrap_or(0) + 1;
+    if cols.saturating_mul(rows) > MAX_PRINTED_AREA {
+        println!("The folded paper is {}x{}, too big to print", cols, rows);
+        return;
+    }
+    let mut display: Vec<Vec<char>> = Vec::with_capacity(rows as usize);
     for _ in 0..rows {
-        display.push(std::iter::repeat(' ').take(cols).collect::<Vec<_>>());
+        display.push(std::iter::repeat_n(' ', cols as usize).collect::<Vec<_>>());
     }
-    for (col, row) in dots {
-        display[*row][*col] = '#';
+    for dot in dots {
+        display[dot.y as usize][dot.x as usize] = '#';
     }
     for row in display {
         for c in row {
@@ -34,57 +65,113 @@ fn display_dots(dots: &HashSet<(usize, usize)>) {
     }
 }
 
-fn perform_fold(dots: &mut HashSet<(usize, usize)>, fold: (char, usize)) {
-    let index = if fold.0 == 'x' { 0usize } else { 1usize };
-    let position = fold.1;
-    let dots_copy = dots.clone();
-    for (dot_x, dot_y) in dots_copy {
-        let mut coords: [usize; 2] = [dot_x, dot_y];
-        if coords[index] >= position {
-            dots.remove(&(dot_x, dot_y));
-            if coords[index] >= position {
-                coords[index] -= (coords[index] - position) * 2;
-                dots.insert((coords[0], coords[1]));
-            }
+fn record_stage(stages: &mut Vec<Dots>, dots: &Dots) {
+    visualize_dots(dots);
+    if svg::enabled() {
+        stages.push(dots.clone());
+    }
+}
+
+fn export_svg(stages: &[Dots], folds: &[Fold]) {
+    // Each stage gets its own panel, scaled to the same width and stacked
+    // vertically, with the fold that's about to happen drawn across it.
+    const PANEL_WIDTH: f64 = 400.0;
+    const GAP: f64 = 30.0;
+    let mut total_height = 0.0;
+    let mut panels: Vec<(f64, f64, f64)> = Vec::with_capacity(stages.len());
+    for dots in stages {
+        let cols = (dots.iter().map(|dot| dot.x).max().unwrap_or(0) + 1) as f64;
+        let rows = (dots.iter().map(|dot| dot.y).max().unwrap_or(0) + 1) as f64;
+        let scale = PANEL_WIDTH / cols;
+        panels.push((total_height, scale, rows));
+        total_height += rows * scale + GAP;
+    }
+
+    let mut document = svg::Document::new(-10.0, -GAP, PANEL_WIDTH + 20.0, total_height + GAP);
+    for (index, (dots, &(top, scale, rows))) in stages.iter().zip(panels.iter()).enumerate() {
+        let label = if index == 0 { format!("{} dots before folding", dots.len()) } else { format!("{} dots after fold {}", dots.len(), index) };
+        document.text(0.0, top - 8.0, 14.0, &label);
+        document.begin_group(&format!("translate(0,{}) scale({})", top, scale));
+        document.rect(0.0, 0.0, PANEL_WIDTH / scale, rows, svg::Style::stroke("grey", 1.0 / scale));
+        for dot in dots {
+            document.rect(dot.x as f64, dot.y as f64, 1.0, 1.0, svg::Style::fill("black"));
+        }
+        if let Some(&(direction, position)) = folds.get(index) {
+            let position = position as f64 + 0.5;
+            let (from, to) = if direction == 'x' { ((position, 0.0), (position, rows)) } else { ((0.0, position), (PANEL_WIDTH / scale, position)) };
+            document.line(from, to, svg::Style::stroke("red", 2.0 / scale).dashed());
         }
+        document.end_group();
     }
+    svg::save("day13", &document);
 }
 
-fn parse_dots(dots_input: &[String]) -> HashSet<(usize, usize)> {
-    HashSet::from_iter(
-        dots_input
-        .iter()
-        .map(|line|
-            line
-            .split(',')
-            .map(|number|
-                number
-                .parse::<usize>()
-                .expect("Non-numeric input"))
-            .tuples()
-            .next()
-            .expect("Invalid input")))
+fn visualize_dots(dots: &Dots) {
+    if visualize::enabled() {
+        let cols = dots.iter().map(|dot| dot.x).max().unwrap_or(0) + 1;
+        let rows = dots.iter().map(|dot| dot.y).max().unwrap_or(0) + 1;
+        let mut frame = visualize::Frame::new(cols as usize, rows as usize, visualize::BLACK);
+        for dot in dots {
+            frame.set(dot.x as usize, dot.y as usize, visualize::WHITE);
+        }
+        visualize::push(frame);
+    }
+}
+
+fn perform_fold(dots: &mut Dots, (direction, position): Fold) {
+    *dots = dots.iter().filter_map(|&dot| {
+        let coord = if direction == 'x' { dot.x } else { dot.y };
+        if coord < position {
+            return Some(dot);
+        }
+        // Dots are never on the fold line itself, but if one were it would
+        // disappear into the crease.
+        if coord == position {
+            return None;
+        }
+        let reflected = audit::sub(audit::mul(position, 2), coord);
+        Some(if direction == 'x' { Vec2::new(reflected, dot.y) } else { Vec2::new(dot.x, reflected) })
+    }).collect();
+}
+
+fn parse_input(input: &Input) -> ParseResult<(Dots, Vec<Fold>)> {
+    let (dots_input, folds_input) = input.lines().expect_sections(2)?.into_iter().next_tuple().unwrap();
+    let dots = parse_dots(dots_input)?;
+    let folds = parse_folds(folds_input, &dots)?;
+    Ok((dots, folds))
+}
+
+fn parse_dots(dots_input: Lines) -> ParseResult<Dots> {
+    dots_input.iter().map(|line| {
+        let (x, y) = line.as_token().split_once(",")?;
+        Ok(Vec2::new(x.parse_in(0..=MAX_COORDINATE)?, y.parse_in(0..=MAX_COORDINATE)?))
+    }).collect()
 }
 
-fn parse_folds(folds_input: &[String]) -> Vec<(char, usize)> {
-    folds_input
-        .iter()
-        .map(|line|
-            line
-            .split_ascii_whitespace()
-            .last()
-            .expect("Invalid input")
-            .split('=')
-            .tuples::<(&str, &str)>()
-            .map(|(direction, number)|
-                (direction
-                    .chars()
-                    .next()
-                    .unwrap(),
-                number
-                    .parse::<usize>()
-                    .expect("Non-numeric fold instruction")))
-            .next()
-            .expect("Invalid input"))
-        .collect()
-}
\ No newline at end of file
+fn parse_folds(folds_input: Lines, dots: &Dots) -> ParseResult<Vec<Fold>> {
+    folds_input.line(0)?;
+
+    // Keep track of the size of the paper, so that we can reject folds that
+    // would leave dots hanging off the far edge.
+    let mut size = [
+        dots.iter().map(|dot| dot.x + 1).max().unwrap_or(0),
+        dots.iter().map(|dot| dot.y + 1).max().unwrap_or(0),
+    ];
+    let folds = folds_input.parse_each(|line| {
+        let token = line.tokens().last().ok_or_else(|| line.error(0, "expected a fold instruction".to_string()))?;
+        let (name, position) = token.split_once("=")?;
+        let direction = match name.text() {
+            "x" => 'x',
+            "y" => 'y',
+            direction => return Err(token.error(format!("expected a fold along x or y, found {:?}", direction))),
+        };
+        let position = position.parse_in(0..=MAX_COORDINATE)?;
+        let index = if direction == 'x' { 0usize } else { 1usize };
+        if size[index] > position * 2 + 1 {
+            return Err(token.error(format!("folding along {}={} would leave dots off the edge of the paper", direction, position)));
+        }
+        size[index] = size[index].min(position);
+        Ok((direction, position))
+    })?;
+    Ok(folds)
+}
@@ -1,52 +1,166 @@
-use std::collections::HashMap;
 use itertools::Itertools;
+use num_bigint::BigUint;
+use std::collections::HashMap;
 
-use crate::utils;
+use crate::utils::answer::Answer;
+use crate::utils::input::Input;
+use crate::utils::matrix::{Approximate, Element, Matrix, Modular};
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::{Line, ParseResult};
+use crate::utils::simulation;
 
-pub fn day14(input_lines: &[String]) -> (u64, u64) {
-    let input_groups = utils::group_lines_split_by_empty_line(input_lines);
+static PART1_STEPS: Param<usize> = Param::new("part1_steps", "insertion steps for part 1", 10, 0);
+static PART2_STEPS: Param<usize> = Param::new("part2_steps", "insertion steps for part 2", 40, 0);
+static MATRIX: Param<bool> = Param::new("matrix", "jump straight to each part's step with matrix powers, for huge numbers of steps", false, false);
+static MODULUS: Param<u64> = Param::new("modulus", "with matrix, report answers modulo this instead (0 for exact answers)", 0, 0);
+pub static PARAMS: [&dyn Parameter; 4] = [&PART1_STEPS, &PART2_STEPS, &MATRIX, &MODULUS];
 
-    let final_char = input_groups[0][0].chars().last().unwrap();
-    let mut polymer: HashMap<(char, char), u64> = HashMap::with_capacity(input_groups[1].len());
-    for tuple in input_groups[0][0].chars().tuple_windows() {
-        let count = polymer.entry(tuple).or_insert(0);
-        *count += 1;
-    }
-    let rules = input_groups[1].iter().map(|line| Rule::parse(line)).collect::<Vec<_>>();
+pub fn day14(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let (template, rules) = parse_input(input)?;
 
-    for _ in 0..10 {
-        polymer = apply_step(polymer, &rules);
+    let final_char = template.chars().last().unwrap();
+    // The polymer doubles in length every step, so it doesn't take many
+    // for the counts to outgrow a u64.
+    let mut polymer: HashMap<(char, char), BigUint> = HashMap::with_capacity(rules.len());
+    for tuple in template.chars().tuple_windows() {
+        let count = polymer.entry(tuple).or_default();
+        *count += 1u8;
     }
-    let part1 = calc_quantity(&polymer, &final_char);
 
-    for _ in 0..30 {
-        polymer = apply_step(polymer, &rules);
+    if MATRIX.get() {
+        let pairs = PairMatrix::new(template, &rules);
+        let answer = |steps| pairs.answer_after_steps(steps, final_char).map_err(|message| input.lines().end_error(message));
+        return Ok((answer(PART1_STEPS.get())?, answer(PART2_STEPS.get())?));
     }
-    let part2 = calc_quantity(&polymer, &final_char);
 
-    (part1,part2)
+    let steps = (PART1_STEPS.get(), PART2_STEPS.get());
+    let (part1, part2) = simulation::answers_after_steps(&mut polymer, steps, |polymer| *polymer = apply_step(std::mem::take(polymer), &rules), |polymer| calc_quantity(polymer.iter().map(|(&pair, count)| (pair, count)), final_char));
+
+    Ok((part1, part2))
+}
+
+fn parse_input(input: &Input) -> ParseResult<(&str, Vec<Rule>)> {
+    let sections = input.lines().expect_sections(2)?;
+    let template = sections[0].line(0)?;
+    if template.text().is_empty() {
+        return Err(template.error(0, "expected a polymer template".to_string()));
+    }
+    Ok((template.text(), sections[1].parse_each(Rule::parse)?))
 }
 
-fn apply_step(polymer: HashMap<(char, char), u64>, rules: &[Rule]) -> HashMap<(char, char), u64> {
-    let mut new_polymer: HashMap<(char, char), u64> = HashMap::with_capacity(polymer.len());
+fn apply_step(polymer: HashMap<(char, char), BigUint>, rules: &[Rule]) -> HashMap<(char, char), BigUint> {
+    let mut new_polymer: HashMap<(char, char), BigUint> = HashMap::with_capacity(polymer.len());
+    let zero = BigUint::default();
 
     for rule in rules {
-        let count = polymer.get(&rule.pair).unwrap_or(&0);
-        *new_polymer.entry(rule.result1).or_insert(0) += count;
-        *new_polymer.entry(rule.result2).or_insert(0) += count;
+        let count = polymer.get(&rule.pair).unwrap_or(&zero);
+        *new_polymer.entry(rule.result1).or_default() += count;
+        *new_polymer.entry(rule.result2).or_default() += count;
     }
 
     new_polymer
 }
 
-fn calc_quantity(polymer: &HashMap<(char, char), u64>, final_char: &char) -> u64 {
-    let mut char_count: HashMap<char, u64> = HashMap::with_capacity(26);
-    for (c, new_count) in polymer.iter().map(|((c, _), count)| (c, count)) {
-        let count = char_count.entry(*c).or_insert(0);
-        *count += new_count;
+/// The difference between the most and least common elements, given the
+/// count of each pair of elements in the polymer.
+fn calc_quantity<'a>(polymer: impl Iterator<Item = ((char, char), &'a BigUint)>, final_char: char) -> Answer {
+    // Every element is the first of a pair, apart from the last one.
+    let mut char_count: HashMap<char, BigUint> = HashMap::with_capacity(26);
+    for ((c, _), count) in polymer {
+        *char_count.entry(c).or_default() += count;
+    }
+    *char_count.entry(final_char).or_default() += 1u8;
+    // Elements only in pairs that no longer appear aren't in the polymer.
+    // There's always the final element, so there's always a count.
+    let counts: Vec<BigUint> = char_count.into_values().filter(|count| *count != BigUint::default()).collect();
+    (counts.iter().max().unwrap() - counts.iter().min().unwrap()).into()
+}
+
+/// The difference between the most and least common elements modulo
+/// `modulus`, given each pair's count both modulo `modulus` and roughly.
+/// Remainders can't say which element is most or least common, so that's
+/// judged from the rough counts, as long as they're far enough apart to be
+/// sure of.
+fn modular_quantity(pairs: &[(char, char)], remainders: &[Modular], sizes: &[Approximate], final_char: char, modulus: u64, tolerance: f64) -> Result<Answer, String> {
+    let mut char_count: HashMap<char, (Modular, Approximate)> = HashMap::with_capacity(26);
+    let zero = (Modular::new(0, modulus), Approximate::new(0));
+    for ((&(c, _), remainder), size) in pairs.iter().zip(remainders).zip(sizes) {
+        let count = char_count.entry(c).or_insert(zero);
+        *count = (count.0.add(remainder), count.1.add(size));
+    }
+    let count = char_count.entry(final_char).or_insert(zero);
+    *count = (count.0.add(&Modular::new(1, modulus)), count.1.add(&Approximate::new(1)));
+    let counts: Vec<(char, Modular, Approximate)> = char_count.into_iter()
+        .filter(|(_, (_, size))| !size.is_zero())
+        .map(|(c, (remainder, size))| (c, remainder, size))
+        .collect();
+
+    // Any counts within `tolerance` of each other could be the wrong way
+    // round, which only matters if they're different.
+    let exact = |&(c, remainder, size): &(char, Modular, Approximate), description: &str| {
+        let rival = counts.iter().find(|&&(other, other_remainder, other_size)| other != c && other_remainder != remainder && (other_size.ratio(&size) - 1.0).abs() <= tolerance);
+        match rival {
+            Some(&(other, _, _)) => Err(format!("{} and {} are too close in number to tell which is the {} common", c, other, description)),
+            None => Ok(remainder),
+        }
+    };
+    let most = counts.iter().max_by(|(_, _, a), (_, _, b)| a.ratio(b).total_cmp(&1.0)).unwrap();
+    let least = counts.iter().min_by(|(_, _, a), (_, _, b)| a.ratio(b).total_cmp(&1.0)).unwrap();
+    let (most, least) = (exact(most, "most")?, exact(least, "least")?);
+    Ok(most.add(&Modular::new(modulus - least.value, modulus)).value.into())
+}
+
+/// `apply_step` as a matrix, over every pair the template or rules mention.
+struct PairMatrix {
+    pairs: Vec<(char, char)>,
+    /// The (row, column) of each 1 added to the matrix; a pair with a rule
+    /// becomes the rule's two results, and any other pair disappears, just
+    /// as in `apply_step`.
+    ones: Vec<(usize, usize)>,
+    initial: Vec<u64>,
+}
+
+impl PairMatrix {
+    fn new(template: &str, rules: &[Rule]) -> Self {
+        let mut pairs: Vec<(char, char)> = template.chars().tuple_windows()
+            .chain(rules.iter().flat_map(|rule| [rule.pair, rule.result1, rule.result2]))
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        let index = |pair: &(char, char)| pairs.binary_search(pair).unwrap();
+
+        let ones = rules.iter().flat_map(|rule| [(index(&rule.result1), index(&rule.pair)), (index(&rule.result2), index(&rule.pair))]).collect();
+        let mut initial = vec![0u64; pairs.len()];
+        for pair in template.chars().tuple_windows() {
+            initial[index(&pair)] += 1;
+        }
+        Self { pairs, ones, initial }
+    }
+
+    /// Each pair's count after `steps` steps, as whichever kind of matrix
+    /// element `element` makes.
+    fn counts_after_steps<T: Element>(&self, steps: usize, element: impl Fn(u64) -> T) -> Vec<T> {
+        let mut matrix = Matrix::new(self.pairs.len(), self.pairs.len(), element(0));
+        for &cell in self.ones.iter() {
+            matrix[cell] = matrix[cell].add(&element(1));
+        }
+        let initial: Vec<T> = self.initial.iter().map(|&count| element(count)).collect();
+        matrix.pow(steps as u64).apply(&initial)
+    }
+
+    fn answer_after_steps(&self, steps: usize, final_char: char) -> Result<Answer, String> {
+        let modulus = MODULUS.get();
+        if modulus == 0 {
+            let counts = self.counts_after_steps(steps, BigUint::from);
+            return Ok(calc_quantity(self.pairs.iter().copied().zip(&counts), final_char));
+        }
+        let remainders = self.counts_after_steps(steps, |count| Modular::new(count, modulus));
+        let sizes = self.counts_after_steps(steps, Approximate::new);
+        // Each step's multiplication can be out by a rounding error or so
+        // per pair, and repeated squaring makes no difference to that.
+        let tolerance = 4.0 * f64::EPSILON * (steps as f64 + 1.0) * self.pairs.len() as f64;
+        modular_quantity(&self.pairs, &remainders, &sizes, final_char, modulus, tolerance)
     }
-    *char_count.entry(*final_char).or_insert(0) += 1;
-    char_count.values().max().unwrap() - char_count.values().min().unwrap()
 }
 
 struct Rule {
@@ -56,15 +170,15 @@ struct Rule {
 }
 
 impl Rule {
-    fn parse(input_line: &str) -> Self {
-        let tokens: Vec<&str> = input_line.split_ascii_whitespace().collect();
-        let pair1 = tokens[0].chars().next().unwrap();
-        let pair2 = tokens[0].chars().nth(1).unwrap();
-        let insertion = tokens[2].chars().next().unwrap();
-        Self {
+    fn parse(input_line: Line) -> ParseResult<Self> {
+        let pair_token = input_line.token(0)?;
+        let insertion_token = input_line.token(2)?;
+        let (pair1, pair2) = pair_token.text().chars().collect_tuple().ok_or_else(|| pair_token.error("expected a pair of elements".to_string()))?;
+        let insertion = insertion_token.text().chars().exactly_one().map_err(|_| insertion_token.error("expected a single element".to_string()))?;
+        Ok(Self {
             pair: (pair1, pair2),
             result1: (pair1, insertion),
             result2: (insertion, pair2),
-        }
+        })
     }
 }
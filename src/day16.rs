@@ -1,73 +1,48 @@
-use itertools::Itertools;
+use num_bigint::BigUint;
 
-pub fn day16(input_lines: &[String]) -> (u64, u64) {
-    let input = Input::parse(&input_lines[0]);
-    let packets = input.get_packets();
+use crate::utils::answer::Answer;
+use crate::utils::bits::{BitReader, InvalidHexDigit};
+use crate::utils::input::Input;
+use crate::utils::parse::{Line, ParseError, ParseResult};
+
+pub fn day16(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let transmission = Transmission::parse(input.lines().line(0)?)?;
+    let packets = transmission.get_packets()?;
 
     let part1 = packets.iter().map(|packet| packet.version_sum()).sum::<u64>();
     let part2 = packets[0].value();
-    (part1, part2)
+    Ok((part1.into(), part2.into()))
 }
 
-struct Input {
-    bytes: Vec<u8>,
-    current_index: usize,
-    current_offset: usize,
+struct Transmission<'a> {
+    line: Line<'a>,
+    reader: BitReader,
 }
 
-impl Input {
-    fn parse(data: &str) -> Self {
-        Self {
-            bytes: data.chars().tuples().map(|(first, second)| (first.to_digit(16).expect("Invalid input") << 4) as u8 + second.to_digit(16).expect("Invalid input") as u8).collect(),
-            current_index: 0,
-            current_offset: 0,
+impl<'a> Transmission<'a> {
+    fn parse(line: Line<'a>) -> ParseResult<Self> {
+        let data = line.text();
+        if data.len() < 2 || !data.len().is_multiple_of(2) {
+            return Err(line.error(0, "expected an even number of hex digits".to_string()));
         }
+        let reader = BitReader::from_hex(data)
+            .map_err(|InvalidHexDigit { index, found }| line.error(index, format!("expected a hex digit, found {:?}", found)))?;
+        Ok(Self { line, reader })
     }
 
-    fn get_bits(&mut self, num_bits: usize) -> u64 {
-        let first_byte = self.bytes[self.current_index] & (0b11111111 >> self.current_offset);
-        if num_bits <= (8 - self.current_offset) {
-            // Everything we want is in the first byte.
-            let value = (first_byte >> (8 - self.current_offset - num_bits)) as u64;
-
-            self.current_offset += num_bits;
-            if self.current_offset == 8 {
-                self.current_offset = 0;
-                self.current_index += 1;
-            }
+    fn error(&self, message: &str) -> ParseError {
+        // Each hex digit holds four bits.
+        self.line.error(self.reader.position() / 4, message.to_string())
+    }
 
-            value
-        } else {
-            // We want everything from the first byte, and maybe then some.
-            let mut value = first_byte as u64;
-            let mut bits_remaining = num_bits - (8 - self.current_offset);
-            while bits_remaining > 0 {
-                self.current_index += 1;
-                if bits_remaining > 8 {
-                    // We want this entire byte, and it's not the last one.
-                    value <<= 8;
-                    value += self.bytes[self.current_index] as u64;
-                    bits_remaining -= 8;
-                } else {
-                    // This is the last byte.
-                    value <<= bits_remaining;
-                    value += (self.bytes[self.current_index] as u64) >> (8 - bits_remaining);
-
-                    if bits_remaining == 8 {
-                        self.current_index += 1;
-                        self.current_offset = 0;
-                    } else {
-                        self.current_offset = bits_remaining;
-                    }
-
-                    bits_remaining = 0;
-                }
-            }
-            value
+    fn get_bits(&mut self, num_bits: usize) -> ParseResult<u64> {
+        match self.reader.read(num_bits) {
+            Some(value) => Ok(value),
+            None => Err(self.error("transmission ended part-way through a packet")),
         }
     }
 
-    fn get_packets(mut self) -> Vec<Packet> {
+    fn get_packets(mut self) -> ParseResult<Vec<Packet>> {
         let mut finished_packets: Vec<Packet> = Vec::new();
         let mut packets_under_construction: Vec<Packet> = Vec::new();
 
@@ -75,19 +50,28 @@ impl Input {
         // parsing a packet, we're done - the final packet cannot fit
         // within a byte, since the last packet must be a literal value
         // and literals cannot fit within a single byte.
-        while self.current_index < self.bytes.len() - 1 {
-            let packet = self.parse_packet();
-            Self::handle_packet(&mut finished_packets, &mut packets_under_construction, packet);
+        while self.reader.position() / 8 < self.reader.len() / 8 - 1 {
+            let packet = self.parse_packet()?;
+            self.handle_packet(&mut finished_packets, &mut packets_under_construction, packet)?;
         }
 
-        finished_packets
+        if !packets_under_construction.is_empty() {
+            return Err(self.error("transmission ended with an operator still missing sub-packets"));
+        }
+        if finished_packets.is_empty() {
+            return Err(self.error("transmission contains no packets"));
+        }
+        Ok(finished_packets)
     }
 
-    fn handle_packet(finished_packets: &mut Vec<Packet>, packets_under_construction: &mut Vec<Packet>, packet: Packet) {
+    fn handle_packet(&self, finished_packets: &mut Vec<Packet>, packets_under_construction: &mut Vec<Packet>, packet: Packet) -> ParseResult<()> {
         if packet.is_complete() {
+            if let Some(message) = packet.validate() {
+                return Err(self.error(message));
+            }
             if let Some(mut parent_packet) = packets_under_construction.pop() {
                 parent_packet.append_sub_packet(packet);
-                Self::handle_packet(finished_packets, packets_under_construction, parent_packet);
+                self.handle_packet(finished_packets, packets_under_construction, parent_packet)?;
             } else {
                 finished_packets.push(packet);
             }
@@ -95,11 +79,12 @@ impl Input {
             packets_under_construction.push(packet);
         }
 
+        Ok(())
     }
 
-    fn parse_packet(&mut self) -> Packet {
-        let version = self.get_bits(3);
-        let packet_type = self.get_bits(3);
+    fn parse_packet(&mut self) -> ParseResult<Packet> {
+        let version = self.get_bits(3)?;
+        let packet_type = self.get_bits(3)?;
 
         if packet_type == 4 {
             // Literal value
@@ -107,7 +92,10 @@ impl Input {
             let mut value = 0u64;
             loop {
                 value_length += 5;
-                let group = self.get_bits(5);
+                if value_length > 5 * 16 {
+                    return Err(self.error("literal value is too large"));
+                }
+                let group = self.get_bits(5)?;
                 value <<= 4;
                 value += group & 0b1111;
                 if group & 0b10000 == 0 {
@@ -115,7 +103,7 @@ impl Input {
                 }
             }
 
-            Packet { version, total_length: 6 + value_length, packet_type: PacketType::LiteralValue(value) }
+            Ok(Packet { version, total_length: 6 + value_length, packet_type: PacketType::LiteralValue(value) })
         } else {
             // Operator
             let op_type = match packet_type {
@@ -129,17 +117,17 @@ impl Input {
                 _ => unreachable!("Invalid operator packet type"),
             };
 
-            let length_type = self.get_bits(1);
+            let length_type = self.get_bits(1)?;
             if length_type == 0 {
                 // Total length of sub-packets
-                let length = self.get_bits(15);
+                let length = self.get_bits(15)?;
                 let sub_packets = SubPackets::new(length as usize);
-                Packet { version, total_length: 22, packet_type: PacketType::Operator(op_type, OperatorSubPacketType::Length, sub_packets) }
+                Ok(Packet { version, total_length: 22, packet_type: PacketType::Operator(op_type, OperatorSubPacketType::Length, sub_packets) })
             } else {
                 // Number of sub-packets
-                let length = self.get_bits(11);
+                let length = self.get_bits(11)?;
                 let sub_packets = SubPackets::new(length as usize);
-                Packet { version, total_length: 18, packet_type: PacketType::Operator(op_type, OperatorSubPacketType::Number, sub_packets) }
+                Ok(Packet { version, total_length: 18, packet_type: PacketType::Operator(op_type, OperatorSubPacketType::Number, sub_packets) })
             }
 
         }
@@ -170,6 +158,19 @@ impl Packet {
         }
     }
 
+    /// Checks that a complete packet has the right number of sub-packets
+    /// for its operator, returning what's wrong if not.
+    fn validate(&self) -> Option<&'static str> {
+        match &self.packet_type {
+            PacketType::LiteralValue(_) => None,
+            PacketType::Operator(op_type, _, sub_packets) => match op_type {
+                OperatorPacketType::Minimum | OperatorPacketType::Maximum if sub_packets.packets.is_empty() => Some("minimum and maximum packets need at least one sub-packet"),
+                OperatorPacketType::GreaterThan | OperatorPacketType::LessThan | OperatorPacketType::EqualTo if sub_packets.packets.len() != 2 => Some("comparison packets need exactly two sub-packets"),
+                _ => None,
+            },
+        }
+    }
+
     fn version_sum(&self) -> u64 {
         match &self.packet_type {
             PacketType::LiteralValue(_) => self.version,
@@ -177,9 +178,11 @@ impl Packet {
         }
     }
 
-    fn value(&self) -> u64 {
+    /// Sums and products of literal values can overflow any fixed-size
+    /// integer, so the value is calculated exactly.
+    fn value(&self) -> BigUint {
         match &self.packet_type {
-            PacketType::LiteralValue(val) => *val,
+            PacketType::LiteralValue(val) => BigUint::from(*val),
             PacketType::Operator(op_type, _, sub_packets) => {
                 let mut sub_values = sub_packets.packets.iter().map(|packet| packet.value());
                 match op_type {
@@ -189,15 +192,15 @@ impl Packet {
                     OperatorPacketType::Maximum => sub_values.max().unwrap(),
                     OperatorPacketType::GreaterThan => {
                         assert!(sub_packets.packets.len() == 2);
-                        if sub_values.next().unwrap() > sub_values.next().unwrap() { 1 } else { 0 }
+                        BigUint::from((sub_values.next().unwrap() > sub_values.next().unwrap()) as u8)
                     },
                     OperatorPacketType::LessThan => {
                         assert!(sub_packets.packets.len() == 2);
-                        if sub_values.next().unwrap() < sub_values.next().unwrap() { 1 } else { 0 }
+                        BigUint::from((sub_values.next().unwrap() < sub_values.next().unwrap()) as u8)
                     },
                     OperatorPacketType::EqualTo => {
                         assert!(sub_packets.packets.len() == 2);
-                        if sub_values.next().unwrap() == sub_values.next().unwrap() { 1 } else { 0 }
+                        BigUint::from((sub_values.next().unwrap() == sub_values.next().unwrap()) as u8)
                     },
                 }
             }
@@ -1,12 +1,25 @@
 use itertools::Itertools;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
 
-// cba to parse my input today
-const MIN_X: isize = 207;
-const MAX_X: isize = 263;
-const MIN_Y: isize = -115;
-const MAX_Y: isize = -63;
+use crate::utils::answer::Answer;
+use crate::utils::audit::{self, cast};
+use crate::utils::input::Input;
+use crate::utils::parse::{ParseResult, Token};
+use crate::utils::svg;
+use crate::utils::vector::Vec2;
+
+// Everything below assumes the target is below and to the right of the
+// launcher, and part 2 searches every velocity that could possibly hit it,
+// so it needs to be reasonably close by, too.
+const MAX_DISTANCE: i64 = 1000;
+
+pub fn day17(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let target = parse_input(input)?;
+    let min_x = *target.x.start();
+    let max_x = *target.x.end();
+    let min_y = *target.y.start();
 
-pub fn day17(_input_lines: &[String]) -> (u64, u64) {
     // Okay, so part 1 today is really dumb.
     //
     // It's obvious that the X velocity is irrelevant - as long as there's an
@@ -27,48 +40,132 @@ pub fn day17(_input_lines: &[String]) -> (u64, u64) {
     // Y velocity in its *previous* step must be -99 - which means the initial
     // Y velocity must have been 99. The highest point it reaches would be the
     // 99th triangular number.
-    let part1_y_velocity = MIN_Y.abs() - 1;
-    let part1 = ((part1_y_velocity * (part1_y_velocity + 1)) / 2) as u64;
+    let part1_y_velocity = min_y.abs() - 1;
+    let part1 = cast!(audit::mul(part1_y_velocity, audit::add(part1_y_velocity, 1)) / 2 => u64);
 
     // The minimum possible initial X velocity is one that reaches a point >=
-    // MIN_X just as drag reduces it to 0 - which is to say, the index of the
-    // first triangular number >= MIN_X. Very approximately, the square root
-    // of 2 * MIN_X. The maximum possible X velocity is, of course, MAX_X.
-    let min_x_velocity = ((MIN_X * 2) as f64).sqrt() as isize;
-    let max_x_velocity = MAX_X;
+    // min_x just as drag reduces it to 0 - which is to say, the index of the
+    // first triangular number >= min_x. Very approximately, the square root
+    // of 2 * min_x. The maximum possible X velocity is, of course, max_x.
+    let min_x_velocity = ((min_x * 2) as f64).sqrt() as i64;
+    let max_x_velocity = max_x;
 
     // We just calculated the maximum Y velocity, and the minimum Y velocity is
-    // obviously MIN_Y.
-    let min_y_velocity = MIN_Y;
-    let max_y_velocity = part1 as isize;
+    // obviously min_y.
+    let min_y_velocity = min_y;
+    let max_y_velocity = part1 as i64;
 
     // This is a much bigger range than we really need to check, but it's
     // not that much computation.
-    let part2 = (min_x_velocity..=max_x_velocity).cartesian_product(min_y_velocity..=max_y_velocity).filter(|(x, y)| Velocity { x: *x, y: *y }.reaches_target()).count() as u64;
+    let part2 = (min_x_velocity..=max_x_velocity).cartesian_product(min_y_velocity..=max_y_velocity).filter(|&(x, y)| reaches_target(Vec2::new(x, y), &target)).count() as u64;
+
+    if svg::enabled() {
+        export_svg(&target, min_x_velocity..=max_x_velocity, min_y_velocity..=max_y_velocity, part1_y_velocity);
+    }
+
+    Ok((part1.into(), part2.into()))
+}
+
+fn export_svg(target: &Target, x_velocities: RangeInclusive<i64>, y_velocities: RangeInclusive<i64>, highest_y_velocity: i64) {
+    // Drawing every miss would just be noise, so we only draw the near misses:
+    // the ones where a neighbouring initial velocity hits.
+    let hits: HashSet<Vec2> = x_velocities.cartesian_product(y_velocities).map(|(x, y)| Vec2::new(x, y)).filter(|&velocity| reaches_target(velocity, target)).collect();
+    let near_misses: HashSet<Vec2> = hits.iter()
+        .flat_map(|&velocity| [Vec2::new(-1, 0), Vec2::new(1, 0), Vec2::new(0, -1), Vec2::new(0, 1)].map(|step| velocity + step))
+        .filter(|velocity| !hits.contains(velocity))
+        .collect();
+    let highest = hits.iter().copied().filter(|velocity| velocity.y == highest_y_velocity).min();
+
+    // SVG's Y axis points down, so every Y co-ordinate is negated.
+    let max_height = (highest_y_velocity * (highest_y_velocity + 1) / 2).max(0) as f64;
+    let min_y = *target.y.start() as f64;
+    let max_x = *target.x.end() as f64;
+    let margin = (max_x.max(max_height - min_y)) / 20.0;
+    let stroke_width = (max_x.max(max_height - min_y)) / 1000.0;
+    let mut document = svg::Document::new(-margin, -max_height - margin, max_x + margin * 2.0, max_height - min_y + margin * 2.0);
+
+    document.rect(
+        *target.x.start() as f64,
+        -(*target.y.end() as f64),
+        (target.x.end() - target.x.start()) as f64,
+        (target.y.end() - target.y.start()) as f64,
+        svg::Style::fill("grey").opacity(0.5));
+    for &velocity in near_misses.iter() {
+        document.polyline(&trajectory(velocity, target), svg::Style::stroke("red", stroke_width).opacity(0.3));
+    }
+    for &velocity in hits.iter() {
+        document.polyline(&trajectory(velocity, target), svg::Style::stroke("green", stroke_width).opacity(0.3));
+    }
+    if let Some(velocity) = highest {
+        document.polyline(&trajectory(velocity, target), svg::Style::stroke("blue", stroke_width * 3.0));
+    }
+    svg::save("day17", &document);
+}
+
+struct Target {
+    x: RangeInclusive<i64>,
+    y: RangeInclusive<i64>,
+}
+
+impl Target {
+    fn contains(&self, position: Vec2) -> bool {
+        self.x.contains(&position.x) && self.y.contains(&position.y)
+    }
+
+    fn overshot(&self, position: Vec2) -> bool {
+        position.x > *self.x.end() || position.y < *self.y.start()
+    }
+}
+
+fn parse_input(input: &Input) -> ParseResult<Target> {
+    // target area: x=20..30, y=-10..-5
+    let line = input.lines().line(0)?;
+    let x_token = line.token(2)?.trim_end_matches(',');
+    let x = parse_range(x_token, "x")?;
+    if *x.start() <= 0 || *x.end() > MAX_DISTANCE {
+        return Err(x_token.error(format!("target must be between 1 and {} to the right", MAX_DISTANCE)));
+    }
+    let y_token = line.token(3)?;
+    let y = parse_range(y_token, "y")?;
+    if *y.end() >= 0 || *y.start() < -MAX_DISTANCE {
+        return Err(y_token.error(format!("target must be between 1 and {} below the launcher", MAX_DISTANCE)));
+    }
+    Ok(Target { x, y })
+}
 
-    (part1, part2)
+fn parse_range(token: Token, expected_name: &str) -> ParseResult<RangeInclusive<i64>> {
+    let (name, range) = token.range_assignment::<i64>()?;
+    if name != expected_name || range.is_empty() {
+        return Err(token.error(format!("expected a range {}=min..max", expected_name)));
+    }
+    Ok(range)
 }
 
-struct Velocity {
-    x: isize,
-    y: isize,
+/// The positions the probe passes through after being launched with
+/// `velocity`, forever.
+fn positions(mut velocity: Vec2) -> impl Iterator<Item = Vec2> {
+    let mut position = Vec2::default();
+    std::iter::from_fn(move || {
+        position += velocity;
+        velocity.x = std::cmp::max(0, velocity.x - 1);
+        velocity.y -= 1;
+        Some(position)
+    })
 }
 
-impl Velocity {
-    fn reaches_target(mut self) -> bool {
-        let mut x = 0isize;
-        let mut y = 0isize;
-        loop {
-            x += self.x;
-            y += self.y;
-            self.x = std::cmp::max(0, self.x - 1);
-            self.y -= 1;
-            if x > MAX_X || y < MIN_Y {
-                return false;
-            }
-            if (MIN_X..=MAX_X).contains(&x) && (MIN_Y..=MAX_Y).contains(&y) {
-                return true;
-            }
+fn reaches_target(velocity: Vec2, target: &Target) -> bool {
+    positions(velocity).take_while(|&position| !target.overshot(position)).any(|position| target.contains(position))
+}
+
+// The points the probe passes through, in SVG co-ordinates, up to either
+// hitting the target or the first point past it.
+fn trajectory(velocity: Vec2, target: &Target) -> Vec<(f64, f64)> {
+    let mut points = vec![(0.0, 0.0)];
+    for position in positions(velocity) {
+        points.push((position.x as f64, -(position.y as f64)));
+        if target.contains(position) || target.overshot(position) {
+            break;
         }
     }
-}
\ No newline at end of file
+    points
+}
@@ -1,39 +1,68 @@
 use itertools::Itertools;
 
-pub fn day18(input_lines: &[String]) -> (u64, u64) {
-    let numbers = parse_input(input_lines);
+use crate::utils::answer::Answer;
+use crate::utils::explain::explain;
+use crate::utils::input::Input;
+use crate::utils::parse::{Line, ParseResult};
+
+pub fn day18(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let numbers = parse_input(input)?;
     let mut numbers_iter = numbers.iter();
     let first = numbers_iter.next().unwrap().clone();
     let part1 = numbers_iter.fold(first, |acc, number| SFNumber::add(&acc, number)).magnitude();
     let part2 = numbers.into_iter().combinations(2).map(|nums| SFNumber::add(&nums[0], &nums[1]).magnitude()).max().unwrap();
-    (part1, part2)
+    Ok((part1.into(), part2.into()))
+}
+
+// Input numbers are already reduced, so no pair is nested inside four others.
+const MAX_DEPTH: usize = 4;
+
+fn parse_input(input: &Input) -> ParseResult<Vec<SFNumber>> {
+    let lines = input.lines();
+    // Part 2 needs at least two numbers to add together.
+    lines.line(1)?;
+    lines.parse_each(parse_number)
 }
 
-fn parse_input(input_lines: &[String]) -> Vec<SFNumber> {
-    let mut numbers: Vec<SFNumber> = Vec::with_capacity(input_lines.len());
-    for line in input_lines {
-        let mut numbers_stack: Vec<SFNumberUnderConstruction> = Vec::new();
-        for c in line.chars() {
-            match c {
-                '[' => numbers_stack.push(SFNumberUnderConstruction::new()),
-                ']' => {
-                    let finished_number = numbers_stack.pop().unwrap();
-                    if let Some(parent_number) = numbers_stack.last_mut() {
-                        parent_number.add_item(SFElementUnderConstruction::Pair(Box::new(finished_number)));
-                    } else {
-                        // This was the top-level number.
-                        numbers.push(finished_number.final_form());
+fn parse_number(line: Line) -> ParseResult<SFNumber> {
+    let mut numbers_stack: Vec<SFNumberUnderConstruction> = Vec::new();
+    let mut finished: Option<SFNumber> = None;
+    for (index, c) in line.text().chars().enumerate() {
+        if finished.is_some() {
+            return Err(line.error(index, "unexpected text after the end of the number".to_string()));
+        }
+        match c {
+            '[' => {
+                if numbers_stack.len() == MAX_DEPTH {
+                    return Err(line.error(index, format!("pairs can't be nested more than {} deep", MAX_DEPTH)));
+                }
+                numbers_stack.push(SFNumberUnderConstruction::new());
+            },
+            ']' => {
+                let finished_number = numbers_stack.pop()
+                    .ok_or_else(|| line.error(index, "unmatched ']'".to_string()))?
+                    .final_form()
+                    .ok_or_else(|| line.error(index, "expected a pair of two elements".to_string()))?;
+                if let Some(parent_number) = numbers_stack.last_mut() {
+                    if !parent_number.add_item(SFElementUnderConstruction::Pair(Box::new(finished_number))) {
+                        return Err(line.error(index, "pair has more than two elements".to_string()));
                     }
-                },
-                ',' => (),
-                _ => {
-                    let num = c.to_digit(10).expect("Invalid input") as u64;
-                    numbers_stack.last_mut().unwrap().add_item(SFElementUnderConstruction::Single(num));
+                } else {
+                    // This was the top-level number.
+                    finished = Some(finished_number);
+                }
+            },
+            ',' => (),
+            _ => {
+                let num = c.to_digit(10).ok_or_else(|| line.error(index, format!("expected a digit or bracket, found {:?}", c)))? as u64;
+                let parent_number = numbers_stack.last_mut().ok_or_else(|| line.error(index, "number must be a pair".to_string()))?;
+                if !parent_number.add_item(SFElementUnderConstruction::Single(num)) {
+                    return Err(line.error(index, "pair has more than two elements".to_string()));
                 }
             }
         }
     }
-    numbers
+    finished.ok_or_else(|| line.error(line.text().len(), "unterminated number".to_string()))
 }
 
 struct SFNumberUnderConstruction {
@@ -46,33 +75,37 @@ impl SFNumberUnderConstruction {
         Self { first: None, second: None }
     }
 
-    fn add_item(&mut self, element: SFElementUnderConstruction) {
+    /// Adds the next element to the pair, returning false if it's already
+    /// full.
+    fn add_item(&mut self, element: SFElementUnderConstruction) -> bool {
         if self.first.is_none() {
             self.first = Some(element);
-        } else {
-            assert!(self.second.is_none());
+        } else if self.second.is_none() {
             self.second = Some(element);
+        } else {
+            return false;
         }
+        true
     }
 
-    fn final_form(self) -> SFNumber {
-        SFNumber {
-            first: self.first.unwrap().final_form(),
-            second: self.second.unwrap().final_form(),
-        }
+    fn final_form(self) -> Option<SFNumber> {
+        Some(SFNumber {
+            first: self.first?.final_form(),
+            second: self.second?.final_form(),
+        })
     }
 }
 
 enum SFElementUnderConstruction {
     Single(u64),
-    Pair(Box<SFNumberUnderConstruction>),
+    Pair(Box<SFNumber>),
 }
 
 impl SFElementUnderConstruction {
     fn final_form(self) -> SFElement {
         match self {
             Self::Single(num) => SFElement::Single(num),
-            Self::Pair(pair) => SFElement::Pair(Box::new(pair.final_form())),
+            Self::Pair(pair) => SFElement::Pair(pair),
         }
     }
 }
@@ -85,6 +118,7 @@ struct SFNumber {
 
 impl SFNumber {
     fn add(lhs: &Self, rhs: &Self) -> Self {
+        explain!("add {} + {}", lhs, rhs);
         SFNumber {
             first: SFElement::Pair(Box::new(lhs.clone())),
             second: SFElement::Pair(Box::new(rhs.clone())),
@@ -97,14 +131,15 @@ impl SFNumber {
                 ExplosionResult::None => {
                     match self.check_for_split() {
                         SplitResult::None => break,
-                        SplitResult::Handled => (),
+                        SplitResult::Handled => explain!("  = {}", self),
                         SplitResult::Unhandled(_) => unreachable!("Split was unhandled"),
                     }
                 },
-                ExplosionResult::Handled | ExplosionResult::LeftUnhandled(_) | ExplosionResult::RightUnhandled(_) => (),
+                ExplosionResult::Handled | ExplosionResult::LeftUnhandled(_) | ExplosionResult::RightUnhandled(_) => explain!("  = {}", self),
                 ExplosionResult::Unhandled(_, _) => unreachable!("Explosion was unhandled"),
             }
         }
+        explain!("reduced to {}", self);
         self
     }
 
@@ -113,6 +148,7 @@ impl SFNumber {
             // Explode. Hopefully we consist of singles!
             if let SFElement::Single(left) = self.first {
                 if let SFElement::Single(right) = self.second {
+                    explain!("explode [{},{}]", left, right);
                     ExplosionResult::Unhandled(left, right)
                 } else {
                     unreachable!("Mid-level number exploded");
@@ -238,6 +274,7 @@ impl SFElement {
                     let left_num = *num / 2;
                     let left = SFElement::Single(left_num);
                     let right = SFElement::Single(*num - left_num);
+                    explain!("split {} into [{},{}]", num, left_num, *num - left_num);
                     SplitResult::Unhandled(SFElement::Pair(Box::new(SFNumber { first: left, second: right })))
                 } else {
                     SplitResult::None
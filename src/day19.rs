@@ -1,3 +1,7 @@
-pub fn day19(_input_lines: &[String]) -> (u64, u64) {
-    (0,0)
+use crate::utils::answer::Answer;
+use crate::utils::input::Input;
+use crate::utils::parse::ParseResult;
+
+pub fn day19(_input: &Input) -> ParseResult<(Answer, Answer)> {
+    Ok((0.into(), 0.into()))
 }
\ No newline at end of file
@@ -1,14 +1,38 @@
-use std::str::FromStr;
+use num_bigint::BigUint;
 
-pub fn day2(input_lines: &[String]) -> (u64, u64) {
-    let instructions: Vec<Instruction> = input_lines.iter().map(|line| Instruction::parse_line(line)).collect();
+use crate::utils::answer::Answer;
+use crate::utils::audit;
+use crate::utils::input::Input;
+use crate::utils::parse::{Line, ParseResult};
+
+pub fn day2(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let instructions = parse_input(input)?;
     let part1 = instructions.iter().fold(SimplePosition::default(), |position, instruction| position + instruction).product();
     let part2 = instructions.iter().fold(ComplexPosition::default(), |position, instruction| position + instruction).product();
-    (part1, part2)
+    Ok((part1, part2))
+}
+
+fn parse_input(input: &Input) -> ParseResult<Vec<Instruction>> {
+    // Follows the course as part 2 does, where the aim is part 1's depth, so
+    // that neither part's position can leave the water or overflow.
+    let (mut horizontal, mut aim, mut depth) = (0u64, 0u64, 0u64);
+    input.lines().parse_each(|line| {
+        let instruction = Instruction::parse_line(line)?;
+        let distance = instruction.distance;
+        match instruction.direction {
+            Direction::Forward => {
+                horizontal = horizontal.checked_add(distance).ok_or_else(|| line.error(0, "submarine goes too far forward to track".to_string()))?;
+                depth = aim.checked_mul(distance).and_then(|dive| depth.checked_add(dive)).ok_or_else(|| line.error(0, "submarine dives too deep to track".to_string()))?;
+            },
+            Direction::Up => aim = aim.checked_sub(distance).ok_or_else(|| line.error(0, "submarine leaves the water".to_string()))?,
+            Direction::Down => aim = aim.checked_add(distance).ok_or_else(|| line.error(0, "submarine dives too deep to track".to_string()))?,
+        }
+        Ok(instruction)
+    })
 }
 
 trait Position {
-    fn product(&self) -> u64;
+    fn product(&self) -> Answer;
 }
 
 #[derive(Default)]
@@ -18,8 +42,9 @@ struct SimplePosition {
 }
 
 impl Position for SimplePosition {
-    fn product(&self) -> u64 {
-        self.horizontal * self.depth
+    fn product(&self) -> Answer {
+        // Each is a u64, but their product might not be.
+        Answer::from(BigUint::from(self.horizontal) * self.depth)
     }
 }
 
@@ -30,7 +55,7 @@ struct ComplexPosition {
 }
 
 impl Position for ComplexPosition {
-    fn product(&self) -> u64 {
+    fn product(&self) -> Answer {
         self.position.product()
     }
 }
@@ -60,12 +85,11 @@ struct Instruction {
 }
 
 impl Instruction {
-    fn parse_line(input_line: &str) -> Self {
-        let pieces = input_line.split(' ').collect::<Vec<_>>();
-        Self {
-            direction: Direction::from_str(pieces[0]).expect("Direction could not be parsed from input"),
-            distance: pieces[1].parse::<u64>().expect("Distance could not be parsed from input"),
-        }
+    fn parse_line(input_line: Line) -> ParseResult<Self> {
+        Ok(Self {
+            direction: input_line.token(0)?.parse::<Direction>()?,
+            distance: input_line.token(1)?.parse::<u64>()?,
+        })
     }
 }
 
@@ -74,9 +98,9 @@ impl std::ops::Add<&Instruction> for SimplePosition {
 
     fn add(self, other: &Instruction) -> Self {
         match other.direction {
-            Direction::Forward => Self { horizontal: self.horizontal + other.distance, depth: self.depth },
-            Direction::Up => Self { horizontal: self.horizontal, depth: self.depth - other.distance },
-            Direction::Down => Self { horizontal: self.horizontal, depth: self.depth + other.distance },
+            Direction::Forward => Self { horizontal: audit::add(self.horizontal, other.distance), depth: self.depth },
+            Direction::Up => Self { horizontal: self.horizontal, depth: audit::sub(self.depth, other.distance) },
+            Direction::Down => Self { horizontal: self.horizontal, depth: audit::add(self.depth, other.distance) },
         }
     }
 }
@@ -86,9 +110,9 @@ impl std::ops::Add<&Instruction> for ComplexPosition {
 
     fn add(self, other: &Instruction) -> Self {
         match other.direction {
-            Direction::Forward => Self { position: SimplePosition { horizontal: self.position.horizontal + other.distance, depth: self.position.depth + (self.aim * other.distance) }, aim: self.aim },
-            Direction::Up => Self { position: self.position, aim: self.aim - other.distance },
-            Direction::Down => Self { position: self.position, aim: self.aim + other.distance },
+            Direction::Forward => Self { position: SimplePosition { horizontal: audit::add(self.position.horizontal, other.distance), depth: audit::add(self.position.depth, audit::mul(self.aim, other.distance)) }, aim: self.aim },
+            Direction::Up => Self { position: self.position, aim: audit::sub(self.aim, other.distance) },
+            Direction::Down => Self { position: self.position, aim: audit::add(self.aim, other.distance) },
         }
     }
 }
\ No newline at end of file
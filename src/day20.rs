@@ -1,92 +1,96 @@
-use crate::utils;
+use crate::utils::answer::Answer;
+use crate::utils::grid::{Grid, Position};
+use crate::utils::input::Input;
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::ParseResult;
+use crate::utils::simulation;
+use crate::utils::visualize;
 
-pub fn day20(input_lines: &[String]) -> (u64, u64) {
-    let input_sections = utils::group_lines_split_by_empty_line(input_lines);
-    let algorithm: Vec<bool> = input_sections[0][0].chars().map(|c| c == '#').collect();
-    let mut pixels: Vec<Vec<bool>> = Vec::new();
-    for line in input_sections[1] {
-        pixels.push(line.chars().map(|c| c == '#').collect());
-    }
-    let mut default_pixel = false;
+static PART1_ENHANCEMENTS: Param<usize> = Param::new("part1_enhancements", "times to enhance the image for part 1", 2, 0);
+static PART2_ENHANCEMENTS: Param<usize> = Param::new("part2_enhancements", "times to enhance the image for part 2", 50, 0);
+pub static PARAMS: [&dyn Parameter; 2] = [&PART1_ENHANCEMENTS, &PART2_ENHANCEMENTS];
 
-    for _ in 0..2 {
-        pixels = enhance_image(pixels, &algorithm, default_pixel);
-        if algorithm[0] { default_pixel = !default_pixel; }
-    }
-    let part1 = count_lit_pixels(&pixels);
+pub fn day20(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let (algorithm, pixels) = parse_input(input)?;
+    // The image is surrounded by an infinite expanse of unlit pixels.
+    let image = Image { pixels, background: false };
+    visualize_image(&image);
 
-    for _ in 2..50 {
-        pixels = enhance_image(pixels, &algorithm, default_pixel);
-        if algorithm[0] { default_pixel = !default_pixel; }
-    }
-    let part2 = count_lit_pixels(&pixels);
+    let steps = (PART1_ENHANCEMENTS.get(), PART2_ENHANCEMENTS.get());
+    let (part1, part2) = simulation::answers_after_steps(&mut image.clone(), steps, |image| {
+        *image = enhance_image(image, &algorithm);
+        visualize_image(image);
+    }, count_lit_pixels);
 
-    (part1, part2)
+    Ok((part1.into(), part2.into()))
 }
 
-fn count_lit_pixels(pixels: &[Vec<bool>]) -> u64 {
-    pixels.iter().map(|row| row.iter().filter(|pixel| **pixel).count() as u64).sum::<u64>()
+fn parse_input(input: &Input) -> ParseResult<(Vec<bool>, Grid<bool>)> {
+    let sections = input.lines().expect_sections(2)?;
+    let algorithm = sections[0].char_grid("'#' or '.'", parse_pixel)?;
+    if algorithm.height() != 1 || algorithm.width() != 512 {
+        return Err(sections[0].line(0)?.error(0, "expected a single 512-character enhancement algorithm".to_string()));
+    }
+    let pixels = sections[1].char_grid("'#' or '.'", parse_pixel)?;
+    Ok((algorithm.row(0).to_vec(), pixels))
 }
 
-fn enhance_image(pixels: Vec<Vec<bool>>, algorithm: &[bool], default_pixel: bool) -> Vec<Vec<bool>> {
-    let pixels = grow_image(pixels, default_pixel);
-    let mut new_pixels = pixels.clone();
-    for row in 0..new_pixels.len() {
-        for col in 0..new_pixels.len() {
-            new_pixels[row][col] = get_enhanced_pixel(&pixels, algorithm, row, col, default_pixel);
-        }
+fn parse_pixel(c: char) -> Option<bool> {
+    match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
     }
-    new_pixels
 }
 
-fn _print_image(pixels: &[Vec<bool>]) {
-    for row in pixels {
-        for pixel in row {
-            if *pixel { print!("#"); } else { print!("."); }
-        }
-        println!();
+/// The part of the image that's been worked out, and the background filling
+/// the rest of the infinite plane around it. Every step only changes the
+/// pixels near what's been worked out, so keeping a finite grid between
+/// steps avoids copying it in and out of an `InfiniteGrid`.
+#[derive(Clone)]
+struct Image {
+    pixels: Grid<bool>,
+    background: bool,
+}
+
+/// Counts the lit pixels. If the background is lit there are infinitely
+/// many, so this only counts those in the part that's been worked out.
+fn count_lit_pixels(image: &Image) -> u64 {
+    image.pixels.iter().filter(|pixel| **pixel).count() as u64
+}
+
+fn enhance_image(image: &Image, algorithm: &[bool]) -> Image {
+    // Every pixel far enough out is surrounded by background, so they all
+    // enhance to the same new background pixel.
+    let background_index = if image.background { 511 } else { 0 };
+    let new_background = algorithm[background_index];
+
+    // Only the pixels next to the image can become anything other than the
+    // new background, and working out each of those needs the pixels next to
+    // it, so work on a copy two pixels bigger on every side.
+    let pixels = image.pixels.padded(2, image.background);
+    let mut new_pixels = Grid::new(pixels.width() - 2, pixels.height() - 2, new_background);
+    for (x, y) in new_pixels.positions() {
+        new_pixels[(x, y)] = get_enhanced_pixel(&pixels, algorithm, (x + 1, y + 1));
     }
+    Image { pixels: new_pixels, background: new_background }
 }
 
-fn get_pixel(pixels: &[Vec<bool>], row: usize, col: usize, default_pixel: bool) -> bool {
-    if (0..pixels.len()).contains(&row) && (0..pixels[0].len()).contains(&col) {
-        pixels[row][col]
-    } else {
-        default_pixel
+fn visualize_image(image: &Image) {
+    if visualize::enabled() {
+        visualize::push(visualize::Frame::from_grid(&image.pixels, |&pixel| if pixel { visualize::WHITE } else { visualize::BLACK }));
     }
 }
 
-fn get_enhanced_pixel(pixels: &[Vec<bool>], algorithm: &[bool], row: usize, col: usize, default_pixel: bool) -> bool {
+fn get_enhanced_pixel(pixels: &Grid<bool>, algorithm: &[bool], position: Position) -> bool {
     let mut index = 0usize;
-    let irow = row as isize;
-    let icol = col as isize;
-    for g_row in (irow - 1)..=(irow + 1) {
-        for g_col in (icol - 1)..=(icol + 1) {
+    for dy in -1..=1 {
+        for dx in -1..=1 {
             index <<= 1;
-            if get_pixel(pixels, g_row as usize, g_col as usize, default_pixel) {
+            if pixels[pixels.offset(position, (dx, dy)).expect("Enhanced pixels are never at the edge")] {
                 index += 1;
             }
         }
     }
     algorithm[index]
 }
-
-fn grow_image(pixels: Vec<Vec<bool>>, default_pixel: bool) -> Vec<Vec<bool>> {
-    let mut new_image: Vec<Vec<bool>> = Vec::with_capacity(pixels.len() + 2);
-    let new_width = pixels[0].len() + 2;
-    let empty_row = std::iter::repeat(default_pixel).take(new_width).collect::<Vec<_>>();
-
-    new_image.push(empty_row.clone());
-
-    for row in pixels {
-        let mut new_row: Vec<bool> = Vec::with_capacity(new_width);
-        new_row.push(default_pixel);
-        new_row.extend(row.iter());
-        new_row.push(default_pixel);
-        new_image.push(new_row);
-    }
-
-    new_image.push(empty_row);
-
-    new_image
-}
\ No newline at end of file
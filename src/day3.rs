@@ -1,7 +1,10 @@
-pub fn day3(input_lines: &[String]) -> (u64, u64) {
-    let num_bits = input_lines[0].trim().len();
+use crate::utils::answer::Answer;
+use crate::utils::input::Input;
+use crate::utils::parse::ParseResult;
+
+pub fn day3(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let (num_bits, numbers) = parse_input(input)?;
     let full_mask = u64::from_str_radix("1".repeat(num_bits).as_str(), 2).unwrap();
-    let numbers: Vec<u64> = input_lines.iter().map(|line| u64::from_str_radix(line, 2).expect("Input line not binary")).collect();
 
     let mut gamma = 0u64;
     let mut oxygen_generator_numbers = numbers.clone();
@@ -23,14 +26,32 @@ pub fn day3(input_lines: &[String]) -> (u64, u64) {
         }
         if co2_scrubber_numbers.len() > 1 {
             let (co2_set, co2_not_set) = split_by_bit_set(&co2_scrubber_numbers, mask);
-            co2_scrubber_numbers = if co2_set.len() < co2_not_set.len() { co2_set } else { co2_not_set };
+            // If every remaining number agrees on this bit, keep them all.
+            co2_scrubber_numbers = if co2_not_set.is_empty() || (!co2_set.is_empty() && co2_set.len() < co2_not_set.len()) { co2_set } else { co2_not_set };
         }
     }
     let epsilon = !gamma & full_mask;
 
     let part1 = gamma * epsilon;
     let part2 = oxygen_generator_numbers[0] * co2_scrubber_numbers[0];
-    (part1, part2)
+    Ok((part1.into(), part2.into()))
+}
+
+fn parse_input(input: &Input) -> ParseResult<(usize, Vec<u64>)> {
+    let lines = input.lines();
+    let first_line = lines.line(0)?;
+    let num_bits = first_line.text().len();
+    // Gamma and epsilon have to be multiplied together.
+    if num_bits == 0 || num_bits > 32 {
+        return Err(first_line.error(0, "expected a binary number of between 1 and 32 bits".to_string()));
+    }
+    let numbers = lines.parse_each(|line| {
+        if line.text().len() != num_bits {
+            return Err(line.error(0, format!("expected a binary number of {} bits", num_bits)));
+        }
+        u64::from_str_radix(line.text(), 2).map_err(|_| line.error(0, "expected a binary number".to_string()))
+    })?;
+    Ok((num_bits, numbers))
 }
 
 fn split_by_bit_set(values: &[u64], mask: u64) -> (Vec<u64>, Vec<u64>) {
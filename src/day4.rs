@@ -1,23 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::utils;
+use crate::utils::answer::Answer;
+use crate::utils::audit;
+use crate::utils::explain::explain;
+use crate::utils::input::Input;
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::{Lines, ParseResult};
 
-const BOARD_SIZE: usize = 5;
+static BOARD_SIZE: Param<usize> = Param::new("board_size", "width and height of the bingo boards", 5, 1);
+pub static PARAMS: [&dyn Parameter; 1] = [&BOARD_SIZE];
 
-pub fn day4(input_lines: &[String]) -> (u64, u64) {
-    let input_parts = utils::group_lines_split_by_empty_line(input_lines);
-    let mut input_parts_iter = input_parts.iter();
-    let called_numbers: Vec<u64> = input_parts_iter.next().expect("Empty input")[0].split(',').map(|num| num.parse::<u64>().expect("Non-numeric input found")).collect();
-    let mut boards: HashMap<usize, Board> = input_parts_iter.map(|board_input| parse_board(*board_input)).enumerate().collect();
+pub fn day4(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let (called_numbers, mut boards) = parse_input(input)?;
 
     let mut part1: Option<u64> = None;
     let mut part2 = 0u64;
-    for number in called_numbers {
+    for (draw, number) in called_numbers.into_iter().enumerate() {
         let mut completed_boards: Vec<usize> = Vec::new();
         for (board_index, board) in boards.iter_mut() {
             if let Some(sum_of_remaining_numbers) = board.mark_number(number) {
                 completed_boards.push(*board_index);
-                let score = sum_of_remaining_numbers * number;
+                let score = audit::mul(sum_of_remaining_numbers, number);
+                explain!("board {} completed on draw {} (number {}), score {} * {} = {}", board_index + 1, draw + 1, number, sum_of_remaining_numbers, number, score);
                 if part1.is_none() {
                     part1 = Some(score);
                 } else {
@@ -29,18 +33,34 @@ pub fn day4(input_lines: &[String]) -> (u64, u64) {
             boards.remove(&board_index);
         }
     }
-    (part1.unwrap(), part2)
+    let part1 = part1.ok_or_else(|| input.lines().end_error("no board ever wins".to_string()))?;
+    Ok((part1.into(), part2.into()))
 }
 
-fn parse_board(input_lines: &[String]) -> Board {
-    let mut board = Board::default();
+fn parse_input(input: &Input) -> ParseResult<(Vec<u64>, BTreeMap<usize, Board>)> {
+    let sections = input.lines().sections();
+    let called_numbers = sections[0].line(0)?.csv::<u64>()?;
+    // Boards are kept in order, so that when several finish on the same draw,
+    // the last of them in the input is the last to win.
+    let mut boards: BTreeMap<usize, Board> = BTreeMap::new();
+    for (index, board_input) in sections[1..].iter().enumerate() {
+        boards.insert(index, parse_board(*board_input)?);
+    }
+    Ok((called_numbers, boards))
+}
+
+fn parse_board(input_lines: Lines) -> ParseResult<Board> {
+    let board_size = BOARD_SIZE.get();
+    let mut board = Board::new(board_size);
     for (row_num, row) in input_lines.iter().enumerate() {
-        let numbers: Vec<u64> = row.split_whitespace().map(|num| num.parse::<u64>().expect("Non-numeric input found")).collect();
-        for (col_num, number) in numbers.iter().enumerate() {
-            board.numbers.insert(*number, Number { row: row_num, col: col_num });
+        for (col_num, token) in row.tokens().enumerate() {
+            if row_num >= board_size || col_num >= board_size {
+                return Err(token.error(format!("boards must be {}x{}", board_size, board_size)));
+            }
+            board.numbers.insert(token.parse::<u64>()?, Number { row: row_num, col: col_num });
         }
     }
-    board
+    Ok(board)
 }
 
 struct Number {
@@ -48,20 +68,24 @@ struct Number {
     col: usize,
 }
 
-#[derive(Default)]
 struct Board {
     numbers: HashMap<u64, Number>,
-    marked_by_row: [usize; BOARD_SIZE],
-    marked_by_col: [usize; BOARD_SIZE],
+    marked_by_row: Vec<usize>,
+    marked_by_col: Vec<usize>,
 }
 
 impl Board {
+    fn new(size: usize) -> Self {
+        Self { numbers: HashMap::new(), marked_by_row: vec![0; size], marked_by_col: vec![0; size] }
+    }
+
     fn mark_number(&mut self, number: u64) -> Option<u64> {
         if let Some(num) = self.numbers.remove(&number) {
             self.marked_by_row[num.row] += 1;
             self.marked_by_col[num.col] += 1;
-            if (self.marked_by_row[num.row] == BOARD_SIZE) || (self.marked_by_col[num.col] == BOARD_SIZE) {
-                Some(self.numbers.keys().sum())
+            let size = self.marked_by_row.len();
+            if (self.marked_by_row[num.row] == size) || (self.marked_by_col[num.col] == size) {
+                Some(self.numbers.keys().fold(0, |sum, &number| audit::add(sum, number)))
             } else {
                 None
             }
@@ -1,78 +1,102 @@
-use itertools::Itertools;
-use std::cmp;
+use crate::utils::answer::Answer;
+use crate::utils::audit::cast;
+use crate::utils::grid::Grid;
+use crate::utils::input::Input;
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::{Line, ParseResult, Token};
+use crate::utils::svg;
+use crate::utils::vector::Vec2;
 
-const FIELD_SIZE: usize = 1000;
+static FIELD_SIZE: Param<usize> = Param::new("field_size", "width and height of the ocean floor", 1000, 1);
+pub static PARAMS: [&dyn Parameter; 1] = [&FIELD_SIZE];
 
-pub fn day5(input_lines: &[String]) -> (u64, u64) {
-    let mut locations: [[u8; FIELD_SIZE]; FIELD_SIZE] = [[0; FIELD_SIZE]; FIELD_SIZE];
-    let part1 = add_and_calc_intersections(input_lines, &mut locations, false);
-    let part2 = add_and_calc_intersections(input_lines, &mut locations, true);
-    (part1, part2) 
+pub fn day5(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let segments = parse_input(input)?;
+    let field_size = FIELD_SIZE.get();
+    let mut locations: Grid<u8> = Grid::new(field_size, field_size, 0);
+    let part1 = add_and_calc_intersections(&segments, &mut locations, false);
+    let part2 = add_and_calc_intersections(&segments, &mut locations, true);
+    if svg::enabled() {
+        export_svg(&segments, &locations);
+    }
+    Ok((part1.into(), part2.into()))
 }
 
-fn add_and_calc_intersections(input_lines: &[String], locations: &mut [[u8; FIELD_SIZE]; FIELD_SIZE], diagonals: bool) -> u64 {
-    for line in input_lines {
-        let positions = parse_line(line, diagonals);
+fn add_and_calc_intersections(segments: &[Segment], locations: &mut Grid<u8>, diagonals: bool) -> u64 {
+    for segment in segments {
+        let positions = segment.positions(diagonals);
         for position in positions {
-            locations[position.x][position.y] += 1;
+            // We only care whether points are covered more than once.
+            let location = &mut locations[(cast!(position.x => usize), cast!(position.y => usize))];
+            *location = location.saturating_add(1);
         }
     }
 
-    let mut intersections = 0u64;
-    for (x, y) in (0..FIELD_SIZE).cartesian_product(0..FIELD_SIZE) {
-        if locations[x][y] > 1 {
-            intersections += 1;
+    locations.iter().filter(|&&count| count > 1).count() as u64
+}
+
+fn export_svg(segments: &[Segment], locations: &Grid<u8>) {
+    let mut document = svg::Document::new(0.0, 0.0, locations.width() as f64, locations.height() as f64);
+    // Lines are drawn semi-transparently, so overlaps show up darker, and
+    // every point covered more than once is marked.
+    for segment in segments {
+        let colour = if segment.is_diagonal() { "orange" } else { "blue" };
+        let from = (segment.start.x as f64 + 0.5, segment.start.y as f64 + 0.5);
+        let to = (segment.end.x as f64 + 0.5, segment.end.y as f64 + 0.5);
+        document.line(from, to, svg::Style::stroke(colour, 1.0).opacity(0.5));
+    }
+    for (x, y) in locations.positions() {
+        if locations[(x, y)] > 1 {
+            document.rect(x as f64, y as f64, 1.0, 1.0, svg::Style::fill("red"));
         }
     }
-
-    intersections
+    svg::save("day5", &document);
 }
 
-struct Position {
-    x: usize,
-    y: usize,
+struct Segment {
+    start: Vec2,
+    end: Vec2,
 }
 
-fn parse_line(line: &str, diagonals: bool) -> Vec<Position> {
-    let mut tokens = line.split_whitespace();
-    let start = parse_token(tokens.next().expect("Invalid input"));
-    // Second token is the "->"
-    tokens.next();
-    let end = parse_token(tokens.next().expect("Invalid input"));
+impl Segment {
+    fn is_diagonal(&self) -> bool {
+        self.start.x != self.end.x && self.start.y != self.end.y
+    }
 
-    let mut positions: Vec<Position> = Vec::new();
-    if start.x == end.x {
-        if !diagonals {
-            for y in cmp::min(start.y, end.y)..=cmp::max(start.y, end.y) {
-                positions.push(Position { x: start.x, y });
-            }
-        }
-    } else if start.y == end.y {
-        if !diagonals {
-            for x in cmp::min(start.x, end.x)..=cmp::max(start.x, end.x) {
-                positions.push(Position { x, y: start.y });
-            }
-        }
-    } else if diagonals {
-        let mut x = start.x as isize;
-        let mut y = start.y as isize;
-        let delta_x: isize = if end.x > start.x { 1 } else { -1 };
-        let delta_y: isize = if end.y > start.y { 1 } else { -1 };
-        let line_length = cmp::max(start.x, end.x) - cmp::min(start.x, end.x) + 1;
-        for _ in 0..line_length {
-            positions.push(Position { x: x as usize, y: y as usize });
-            x += delta_x;
-            y += delta_y;
+    fn positions(&self, diagonals: bool) -> Vec<Vec2> {
+        if self.is_diagonal() != diagonals {
+            return Vec::new();
         }
+        // Lines are horizontal, vertical or at 45 degrees, so each step
+        // moves at most one along each axis.
+        let step = (self.end - self.start).signum();
+        let line_length = self.start.x.abs_diff(self.end.x).max(self.start.y.abs_diff(self.end.y)) as i64 + 1;
+        (0..line_length).map(|distance| self.start + step * distance).collect()
     }
+}
 
-    positions
+fn parse_input(input: &Input) -> ParseResult<Vec<Segment>> {
+    input.lines().parse_each(parse_line)
 }
 
-fn parse_token(token: &str) -> Position {
-    let mut coords = token.split(',');
-    Position { 
-        x: coords.next().expect("Invalid input").parse::<usize>().expect("Non-numeric co-ordinate"),
-        y: coords.next().expect("Invalid input").parse::<usize>().expect("Non-numeric co-ordinate"),
+fn parse_line(line: Line) -> ParseResult<Segment> {
+    // Second token is the "->"
+    let segment = Segment {
+        start: parse_token(line.token(0)?)?,
+        end: parse_token(line.token(2)?)?,
+    };
+    let Segment { start, end } = &segment;
+    if segment.is_diagonal() && start.x.abs_diff(end.x) != start.y.abs_diff(end.y) {
+        return Err(line.error(0, "lines must be horizontal, vertical or at 45 degrees".to_string()));
     }
-}
\ No newline at end of file
+    Ok(segment)
+}
+
+fn parse_token(token: Token) -> ParseResult<Vec2> {
+    let (x, y) = token.pair::<usize>()?;
+    let field_size = FIELD_SIZE.get();
+    if x >= field_size || y >= field_size {
+        return Err(token.error(format!("co-ordinates must be less than {}", field_size)));
+    }
+    Ok(Vec2::new(x as i64, y as i64))
+}
@@ -1,27 +1,76 @@
-pub fn day6(input_lines: &[String]) -> (u64, u64) {
-    let mut fish_by_time: [u64; 9] = [0; 9];
-    input_lines[0].split(',').map(|time| time.parse::<usize>().expect("Invalid input")).for_each(|time| fish_by_time[time] += 1);
+use num_bigint::BigUint;
 
-    for _ in 0..80 {
-        simulate_day(&mut fish_by_time);
-    }
-    let part1 = fish_by_time.iter().sum();
+use crate::utils::answer::Answer;
+use crate::utils::input::Input;
+use crate::utils::matrix::{Element, Matrix, Modular};
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::ParseResult;
+use crate::utils::simulation;
 
-    for _ in 80..256 {
-        simulate_day(&mut fish_by_time);
+static PART1_DAYS: Param<usize> = Param::new("part1_days", "days to simulate for part 1", 80, 0);
+static PART2_DAYS: Param<usize> = Param::new("part2_days", "days to simulate for part 2", 256, 0);
+static MATRIX: Param<bool> = Param::new("matrix", "jump straight to each part's day with matrix powers, for huge numbers of days", false, false);
+static MODULUS: Param<u64> = Param::new("modulus", "with matrix, report answers modulo this instead (0 for exact answers)", 0, 0);
+pub static PARAMS: [&dyn Parameter; 4] = [&PART1_DAYS, &PART2_DAYS, &MATRIX, &MODULUS];
+
+pub fn day6(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let fish_by_time = parse_input(input)?;
+    if MATRIX.get() {
+        return Ok((matrix_answer(&fish_by_time, PART1_DAYS.get()), matrix_answer(&fish_by_time, PART2_DAYS.get())));
     }
-    let part2 = fish_by_time.iter().sum();
+    // The number of fish grows exponentially, so it only takes a few
+    // thousand days to outgrow a u64.
+    let mut fish_by_time: [BigUint; 9] = fish_by_time.map(BigUint::from);
+    let (part1, part2) = simulation::answers_after_steps(&mut fish_by_time, (PART1_DAYS.get(), PART2_DAYS.get()), simulate_day, |fish_by_time| fish_by_time.iter().sum::<BigUint>());
+    Ok((part1.into(), part2.into()))
+}
 
-    (part1, part2)
+fn parse_input(input: &Input) -> ParseResult<[u64; 9]> {
+    let mut fish_by_time: [u64; 9] = [0; 9];
+    let line = input.lines().line(0)?;
+    for token in line.as_token().split(",") {
+        let time = token.parse::<usize>()?;
+        if time >= fish_by_time.len() {
+            return Err(token.error(format!("fish timers must be less than {}", fish_by_time.len())));
+        }
+        fish_by_time[time] += 1;
+    }
+    Ok(fish_by_time)
 }
 
-fn simulate_day(fish_by_time: &mut [u64; 9]) {
+fn simulate_day(fish_by_time: &mut [BigUint; 9]) {
     fish_by_time.rotate_left(1);
-    fish_by_time[6] += fish_by_time[8];
+    let spawning_fish = fish_by_time[8].clone();
+    fish_by_time[6] += spawning_fish;
     // let spawning_fish = fish_by_time[0];
     // for time in 0..8 {
     //     fish_by_time[time] = fish_by_time[time + 1];
     // }
     // fish_by_time[8] = spawning_fish;
     // fish_by_time[6] += spawning_fish;
-}
\ No newline at end of file
+}
+
+/// The number of fish after `days` days, using whichever kind of matrix
+/// element `element` makes.
+fn count_after_days<T: Element>(fish_by_time: &[u64; 9], days: usize, element: impl Fn(u64) -> T) -> T {
+    // This is `simulate_day` as a matrix: each timer's fish move down a
+    // timer, and the fish at 0 go to both 6 and 8.
+    let mut matrix = Matrix::new(9, 9, element(0));
+    for time in 0..8 {
+        matrix[(time, time + 1)] = element(1);
+    }
+    matrix[(6, 0)] = element(1);
+    matrix[(8, 0)] = element(1);
+
+    let fish_by_time: Vec<T> = fish_by_time.iter().map(|&count| element(count)).collect();
+    matrix.pow(days as u64).apply(&fish_by_time).iter().fold(element(0), |total, count| total.add(count))
+}
+
+fn matrix_answer(fish_by_time: &[u64; 9], days: usize) -> Answer {
+    let modulus = MODULUS.get();
+    if modulus > 0 {
+        count_after_days(fish_by_time, days, |value| Modular::new(value, modulus)).value.into()
+    } else {
+        count_after_days(fish_by_time, days, BigUint::from).into()
+    }
+}
@@ -1,24 +1,34 @@
-pub fn day7(input_lines: &[String]) -> (u64, u64) {
-    let mut positions = input_lines[0].split(',').map(|pos_str| pos_str.parse::<i64>().expect("Non-numeric input")).collect::<Vec<_>>();
+use crate::utils::answer::Answer;
+use crate::utils::audit::{self, cast};
+use crate::utils::input::Input;
+use crate::utils::parse::ParseResult;
+
+/// Part 2's fuel grows with the square of the distance moved, so positions
+/// are kept small enough for any realistic number of crabs' fuel to fit.
+const MAX_POSITION: i64 = 1_000_000;
+
+pub fn day7(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let line = input.lines().line(0)?;
+    let mut positions: Vec<i64> = line.as_token().split(",").map(|token| token.parse_in(0..=MAX_POSITION)).collect::<ParseResult<_>>()?;
     positions.sort_unstable();
 
     // I know this isn't quite right as a median calculation. Sue me, it worked.
-    let median = positions[(positions.len() / 2)];
+    let median = positions[positions.len() / 2];
     let part1 = determine_fuel_simple(&positions, median);
 
-    let mean = positions.iter().sum::<i64>() / positions.len() as i64;
+    let mean = positions.iter().fold(0, |total, &position| audit::add(total, position)) / positions.len() as i64;
     let part2 = std::cmp::min(determine_fuel_complex(&positions, mean), determine_fuel_complex(&positions, mean + 1));
 
-    (part1,part2)
+    Ok((part1.into(), part2.into()))
 }
 
 fn determine_fuel_simple(positions: &[i64], target: i64) -> u64 {
-    positions.iter().map(|pos| (*pos - target).abs()).sum::<i64>() as u64
+    cast!(positions.iter().fold(0, |total, pos| audit::add(total, audit::sub(*pos, target).abs())) => u64)
 }
 
 fn determine_fuel_complex(positions: &[i64], target: i64) -> u64 {
-    positions.iter().map(|pos| {
-        let diff = (*pos - target).abs();
-        (diff * (diff + 1)) / 2
-    }).sum::<i64>() as u64
+    cast!(positions.iter().fold(0, |total, pos| {
+        let diff = audit::sub(*pos, target).abs();
+        audit::add(total, audit::mul(diff, audit::add(diff, 1)) / 2)
+    }) => u64)
 }
\ No newline at end of file
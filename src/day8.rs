@@ -1,11 +1,16 @@
 use itertools::Itertools;
 use std::collections::{HashSet, HashMap};
 
-pub fn day8(input_lines: &[String]) -> (u64, u64) {
-    let displays: Vec<SubDisplay> = input_lines.iter().map(|line| SubDisplay::parse_and_solve(line)).collect();
+use crate::utils::answer::Answer;
+use crate::utils::explain::explain;
+use crate::utils::input::Input;
+use crate::utils::parse::{Line, ParseResult};
+
+pub fn day8(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let displays: Vec<SubDisplay> = input.lines().parse_each(SubDisplay::parse_and_solve)?;
     let part1: u64 = displays.iter().map(|display| display.digits.iter().filter(|&&digit| digit == 1 || digit == 4 || digit == 7 || digit == 8).count() as u64).sum();
     let part2: u64 = displays.iter().map(|display| display.value).sum();
-    (part1,part2)
+    Ok((part1.into(), part2.into()))
 }
 
 struct Digit {
@@ -31,76 +36,90 @@ struct SubDisplay {
 }
 
 impl SubDisplay {
-    fn parse_and_solve(input_line: &str) -> Self {
-        let mut sections = input_line.split(" | ");
-        let inputs: Vec<Digit> = sections.next().unwrap().split_ascii_whitespace().map(|str| Digit::parse(str)).collect();
+    fn parse_and_solve(input_line: Line) -> ParseResult<Self> {
+        let (patterns, outputs) = input_line.as_token().split_once(" | ")?;
+        let inputs: Vec<Digit> = patterns.text().split_ascii_whitespace().map(Digit::parse).collect();
+        if inputs.len() != 10 || outputs.text().split_ascii_whitespace().count() != 4 {
+            return Err(input_line.error(0, "expected ten patterns and four output digits".to_string()));
+        }
+        let unidentified = |digit: u64| input_line.error(0, format!("couldn't identify the pattern for {}", digit));
         let mut map: HashMap<&str, u64> = HashMap::new();
     
         // The input with length 2 is number 1.
-        let one = inputs.iter().find(|digit| digit.string.len() == 2).unwrap();
+        let one = inputs.iter().find(|digit| digit.string.len() == 2).ok_or_else(|| unidentified(1))?;
         map.insert(one.string.as_str(), 1);
+        explain!("length-2 pattern {} is 1", one.string);
     
         // The input with length 3 is number 7.
-        let seven = inputs.iter().find(|digit| digit.string.len() == 3).unwrap();
+        let seven = inputs.iter().find(|digit| digit.string.len() == 3).ok_or_else(|| unidentified(7))?;
         map.insert(seven.string.as_str(), 7);
+        explain!("length-3 pattern {} is 7", seven.string);
     
         // The input with length 4 is number 4.
-        let four = inputs.iter().find(|digit| digit.string.len() == 4).unwrap();
+        let four = inputs.iter().find(|digit| digit.string.len() == 4).ok_or_else(|| unidentified(4))?;
         map.insert(four.string.as_str(), 4);
+        explain!("length-4 pattern {} is 4", four.string);
     
         // The input with length 7 is number 8.
-        let eight = inputs.iter().find(|digit| digit.string.len() == 7).unwrap();
+        let eight = inputs.iter().find(|digit| digit.string.len() == 7).ok_or_else(|| unidentified(8))?;
         map.insert(eight.string.as_str(), 8);
+        explain!("length-7 pattern {} is 8", eight.string);
     
         // Three digits have length 5: 2, 3 and 5.
         let mut len_five: Vec<&Digit> = inputs.iter().filter(|digit| digit.string.len() == 5).collect();
     
         // The one whose segments are a superset of 1 is 3.
-        let three = *len_five.iter().find(|digit| digit.set.is_superset(&one.set)).unwrap();
+        let three = *len_five.iter().find(|digit| digit.set.is_superset(&one.set)).ok_or_else(|| unidentified(3))?;
         map.insert(three.string.as_str(), 3);
-        len_five.retain(|&digit| digit as *const _ != three as *const _);
+        explain!("superset of 1 among length-5 patterns, {}, is 3", three.string);
+        len_five.retain(|&digit| !std::ptr::eq(digit, three));
     
         // The one with three segments in common with 4 is 5.
-        let five = *len_five.iter().find(|digit| digit.set.intersection(&four.set).count() == 3).unwrap();
+        let five = *len_five.iter().find(|digit| digit.set.intersection(&four.set).count() == 3).ok_or_else(|| unidentified(5))?;
         map.insert(five.string.as_str(), 5);
-        len_five.retain(|&digit| digit as *const _ != five as *const _);
+        explain!("length-5 pattern sharing three segments with 4, {}, is 5", five.string);
+        len_five.retain(|&digit| !std::ptr::eq(digit, five));
     
         // The remaining one is 2.
-        let two = len_five[0];
+        let two = *len_five.first().ok_or_else(|| unidentified(2))?;
         map.insert(two.string.as_str(), 2);
+        explain!("remaining length-5 pattern {} is 2", two.string);
     
         // Three digits have length 6: 0, 6 and 9.
         let mut len_six: Vec<&Digit> = inputs.iter().filter(|digit| digit.string.len() == 6).collect();
     
         // The one that is *not* a superset of 5 is 0.
-        let zero = *len_six.iter().find(|digit| !digit.set.is_superset(&five.set)).unwrap();
+        let zero = *len_six.iter().find(|digit| !digit.set.is_superset(&five.set)).ok_or_else(|| unidentified(0))?;
         map.insert(zero.string.as_str(), 0);
-        len_six.retain(|&digit| digit as *const _ != zero as *const _);
+        explain!("length-6 pattern that isn't a superset of 5, {}, is 0", zero.string);
+        len_six.retain(|&digit| !std::ptr::eq(digit, zero));
     
         // The remaining one that is a superset of 7 is 9.
-        let nine = *len_six.iter().find(|digit| digit.set.is_superset(&seven.set)).unwrap();
+        let nine = *len_six.iter().find(|digit| digit.set.is_superset(&seven.set)).ok_or_else(|| unidentified(9))?;
         map.insert(nine.string.as_str(), 9);
-        len_six.retain(|&digit| digit as *const _ != nine as *const _);
+        explain!("remaining length-6 superset of 7, {}, is 9", nine.string);
+        len_six.retain(|&digit| !std::ptr::eq(digit, nine));
     
         // The remaining one is 6.
-        let six = len_six[0];
+        let six = *len_six.first().ok_or_else(|| unidentified(6))?;
         map.insert(six.string.as_str(), 6);
+        explain!("remaining length-6 pattern {} is 6", six.string);
     
-        let digits: Vec<u64> = sections
-            .next()
-            .unwrap()
+        let digits: Vec<u64> = outputs
+            .text()
             .split_ascii_whitespace()
             .map(|str| str.chars().sorted().collect::<String>())
-            .map(|str| *map.get(str.as_str()).unwrap())
-            .collect();
+            .map(|str| map.get(str.as_str()).copied().ok_or_else(|| outputs.error(format!("unrecognised output pattern {}", str))))
+            .collect::<ParseResult<_>>()?;
         let mut value = 0u64;
         for digit in digits.iter() {
             value *= 10;
             value += digit;
         }
-        Self {
+        explain!("output reads {}", value);
+        Ok(Self {
             digits,
             value
-        }
+        })
     }
 }
@@ -1,76 +1,92 @@
-use itertools::Itertools;
 use std::collections::HashSet;
 
-pub fn day9(input_lines: &[String]) -> (u64, u64) {
-    let heights = parse_input(input_lines);
-    let rows = heights.len();
-    let cols = heights[0].len();
+use crate::utils::answer::Answer;
+use crate::utils::grid::{Grid, Position};
+use crate::utils::input::Input;
+use crate::utils::params::{Param, Parameter};
+use crate::utils::parse::ParseResult;
+use crate::utils::union_find::UnionFind;
+use crate::utils::visualize;
 
-    let mut low_points: Vec<Position> = Vec::new();
-    for (row, col) in (0..rows).cartesian_product(0..cols) {
-        let height = heights[row][col];
-        if (row == 0 || height < heights[row-1][col]) &&
-            (row == rows-1 || height < heights[row+1][col]) &&
-            (col == 0 || height < heights[row][col-1]) &&
-            (col == cols-1 || height < heights[row][col+1]) {
-            low_points.push(Position { row, col, height });
+static UNION_FIND: Param<bool> = Param::new("union_find", "find the basins by joining up every cell below 9 in one pass, instead of flooding out from each low point", false, false);
+pub static PARAMS: [&dyn Parameter; 1] = [&UNION_FIND];
+
+pub fn day9(input: &Input) -> ParseResult<(Answer, Answer)> {
+    let heights: Grid<u64> = input.lines().digit_grid()?;
+
+    let mut low_points: Vec<LowPoint> = Vec::new();
+    for position in heights.positions() {
+        let height = heights[position];
+        if heights.neighbours4(position).all(|neighbour| height < heights[neighbour]) {
+            low_points.push(LowPoint { position, height });
         }
     }
-    let part1 = low_points.iter().map(|pos| pos.height).sum::<u64>() + low_points.len() as u64;
+    let part1 = low_points.iter().map(|lp| lp.height).sum::<u64>() + low_points.len() as u64;
 
-    let mut basins: Vec<u64> = Vec::with_capacity(low_points.len());
-    for lp in low_points {
-        let mut basin: HashSet<(usize, usize)> = HashSet::new();
-        basin.insert((lp.row, lp.col));
+    let mut basins = if UNION_FIND.get() { join_basins(&heights) } else { flood_basins(&heights, low_points) };
+    basins.sort_unstable();
+    let part2 = basins.iter().rev().take(3).product::<u64>();
 
-        let mut unexplored_locations: Vec<(usize, usize)> = vec![(lp.row, lp.col)];
-        while !unexplored_locations.is_empty() {
-            let (row, col) = unexplored_locations.pop().unwrap();
+    Ok((part1.into(), part2.into()))
+}
 
-            if row > 0 && !basin.contains(&(row-1, col)) && heights[row-1][col] != 9 {
-                let loc = (row-1, col);
-                basin.insert(loc);
-                unexplored_locations.push(loc);
-            }
-            if row < rows-1 && !basin.contains(&(row+1, col)) && heights[row+1][col] != 9 {
-                let loc = (row+1, col);
-                basin.insert(loc);
-                unexplored_locations.push(loc);
-            }
-            if col > 0 && !basin.contains(&(row, col-1)) && heights[row][col-1] != 9 {
-                let loc = (row, col-1);
-                basin.insert(loc);
-                unexplored_locations.push(loc);
+fn flood_basins(heights: &Grid<u64>, low_points: Vec<LowPoint>) -> Vec<u64> {
+    let mut frame = if visualize::enabled() { Some(visualize::Frame::from_grid(heights, |&height| visualize::shade(height, 9))) } else { None };
+    let mut basins: Vec<u64> = Vec::with_capacity(low_points.len());
+    for (basin_index, lp) in low_points.into_iter().enumerate() {
+        let mut basin: HashSet<Position> = HashSet::new();
+        basin.insert(lp.position);
+
+        let mut unexplored_locations: Vec<Position> = vec![lp.position];
+        while let Some(location) = unexplored_locations.pop() {
+            for neighbour in heights.neighbours4(location) {
+                if heights[neighbour] != 9 && basin.insert(neighbour) {
+                    unexplored_locations.push(neighbour);
+                }
             }
-            if col < cols-1 && !basin.contains(&(row, col+1)) && heights[row][col+1] != 9 {
-                let loc = (row, col+1);
-                basin.insert(loc);
-                unexplored_locations.push(loc);
+        }
+
+        if let Some(frame) = frame.as_mut() {
+            for &(x, y) in basin.iter() {
+                frame.set(x, y, visualize::label_colour(basin_index));
             }
+            visualize::push(frame.clone());
         }
 
         basins.push(basin.len() as u64);
     }
-    basins.sort_unstable();
-    let part2 = basins.iter().rev().take(3).product::<u64>();
-
-    (part1,part2)
+    basins
 }
 
-fn parse_input(input_lines: &[String]) -> Vec<Vec<u64>> {
-    let mut heights_outer: Vec<Vec<u64>> = Vec::with_capacity(input_lines.len());
-    for line in input_lines {
-        let mut heights_inner = Vec::with_capacity(line.len());
-        for height in line.chars().map(|c| c.to_digit(10).expect("Non-numeric input") as u64) {
-            heights_inner.push(height);
+/// Every cell below 9 is in the same basin as its neighbours below 9, so
+/// joining each to the ones right of and below it labels every basin. In a
+/// valid heightmap each of these is the basin of exactly one low point.
+fn join_basins(heights: &Grid<u64>) -> Vec<u64> {
+    let width = heights.width();
+    let index = |(x, y): Position| y * width + x;
+    let mut cells = UnionFind::new(width * heights.height());
+    for (x, y) in heights.positions().filter(|&position| heights[position] != 9) {
+        for neighbour in [(x + 1, y), (x, y + 1)] {
+            if heights.get(neighbour).is_some_and(|&height| height != 9) {
+                cells.union(index((x, y)), index(neighbour));
+            }
+        }
+    }
+
+    let basins: Vec<Vec<usize>> = cells.components().into_iter().filter(|cells| heights[(cells[0] % width, cells[0] / width)] != 9).collect();
+    if visualize::enabled() {
+        let mut frame = visualize::Frame::from_grid(heights, |&height| visualize::shade(height, 9));
+        for (basin_index, basin) in basins.iter().enumerate() {
+            for &cell in basin {
+                frame.set(cell % width, cell / width, visualize::label_colour(basin_index));
+            }
         }
-        heights_outer.push(heights_inner);
+        visualize::push(frame);
     }
-    heights_outer
+    basins.iter().map(|basin| basin.len() as u64).collect()
 }
 
-struct Position {
-    row: usize,
-    col: usize,
+struct LowPoint {
+    position: Position,
     height: u64,
-}
\ No newline at end of file
+}
@@ -0,0 +1,41 @@
+use advent_of_code_2021::utils::answer::Answer;
+use advent_of_code_2021::utils::input::Input;
+use advent_of_code_2021::utils::parse::ParseResult;
+use advent_of_code_2021::DayFunction;
+
+// How many extra times to run each day.
+const RUNS: usize = 5;
+
+/// An extra run of a day that didn't get the same answer as the first.
+pub struct Difference {
+    run: usize,
+    result: ParseResult<(Answer, Answer)>,
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.result {
+            Ok((part1, part2)) => write!(f, "run {} gave part 1: {}, part 2: {}", self.run, part1, part2),
+            Err(error) => write!(f, "run {} failed: {}", self.run, error),
+        }
+    }
+}
+
+/// Runs a day again several times, checking that it gets the same result
+/// every time.
+///
+/// The standard library seeds each thread's hashers randomly, so each run
+/// happens on a thread of its own. Anything that depends on the iteration
+/// order of a `HashMap` or `HashSet` will then see a different order on each
+/// run.
+pub fn check(day_function: DayFunction, input: &Input, expected: &ParseResult<(Answer, Answer)>) -> Vec<Difference> {
+    let mut differences: Vec<Difference> = Vec::new();
+    for run in 1..=RUNS {
+        let result = std::thread::scope(|scope| scope.spawn(|| day_function(input)).join())
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        if result != *expected {
+            differences.push(Difference { run, result });
+        }
+    }
+    differences
+}
@@ -0,0 +1,68 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use advent_of_code_2021::utils::answer::Answer;
+
+pub enum Outcome {
+    Solved { part1: Answer, part2: Answer, load: u128, solve: u128 },
+    Skipped(String),
+    Invalid(String),
+    Overflow(String),
+}
+
+/// What happened on one day, for the `--json` report.
+pub struct DayReport {
+    pub day: usize,
+    pub outcome: Outcome,
+    /// The parameter values the day ran with, already in JSON form.
+    pub params: Vec<(&'static str, String)>,
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Numbers are written out in full, however big, and text as a string.
+fn answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Text(text) => escape(text),
+        _ => answer.to_string(),
+    }
+}
+
+fn render(reports: &[DayReport]) -> String {
+    let mut json = String::from("{\n  \"days\": [");
+    for (index, report) in reports.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        write!(json, "\n    {{\"day\": {}, ", report.day).unwrap();
+        match &report.outcome {
+            Outcome::Solved { part1, part2, load, solve } => write!(json, "\"part1\": {}, \"part2\": {}, \"load_micros\": {}, \"solve_micros\": {}", answer(part1), answer(part2), load, solve),
+            Outcome::Skipped(reason) => write!(json, "\"skipped\": {}", escape(reason)),
+            Outcome::Invalid(error) => write!(json, "\"error\": {}", escape(error)),
+            Outcome::Overflow(overflow) => write!(json, "\"overflow\": {}", escape(overflow)),
+        }.unwrap();
+        let params = report.params.iter().map(|(name, value)| format!("{}: {}", escape(name), value)).collect::<Vec<_>>().join(", ");
+        write!(json, ", \"params\": {{{}}}}}", params).unwrap();
+    }
+    json.push_str("\n  ]\n}\n");
+    json
+}
+
+pub fn write(path: &Path, reports: &[DayReport]) {
+    std::fs::write(path, render(reports)).expect("Can't write JSON report");
+    println!("Wrote {}", path.display());
+}
@@ -0,0 +1,87 @@
+//#[macro_use] extern crate lazy_static;
+//#[macro_use] extern crate strum_macros;
+
+pub mod utils;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+// pub mod day21;
+// pub mod day22;
+// pub mod day23;
+// pub mod day24;
+// pub mod day25;
+
+use utils::answer::Answer;
+use utils::input::Input;
+use utils::params::Parameter;
+use utils::parse::ParseResult;
+
+pub type DayFunction = fn(&Input) -> ParseResult<(Answer, Answer)>;
+pub static DAY_FUNCTIONS: [DayFunction; 20] = [
+    day1::day1,
+    day2::day2,
+    day3::day3,
+    day4::day4,
+    day5::day5,
+    day6::day6,
+    day7::day7,
+    day8::day8,
+    day9::day9,
+    day10::day10,
+    day11::day11,
+    day12::day12,
+    day13::day13,
+    day14::day14,
+    day15::day15,
+    day16::day16,
+    day17::day17,
+    day18::day18,
+    day19::day19,
+    day20::day20,
+    // day21::day21,
+    // day22::day22,
+    // day23::day23,
+    // day24::day24,
+    // day25::day25,
+];
+
+/// Each day's puzzle parameters, in the same order as `DAY_FUNCTIONS`.
+pub static DAY_PARAMS: [&[&dyn Parameter]; 20] = [
+    &[],
+    &[],
+    &[],
+    &day4::PARAMS,
+    &day5::PARAMS,
+    &day6::PARAMS,
+    &[],
+    &[],
+    &day9::PARAMS,
+    &[],
+    &day11::PARAMS,
+    &[],
+    &[],
+    &day14::PARAMS,
+    &day15::PARAMS,
+    &[],
+    &[],
+    &[],
+    &[],
+    &day20::PARAMS,
+];
@@ -1,91 +1,98 @@
-//#[macro_use] extern crate lazy_static;
-//#[macro_use] extern crate strum_macros;
-
-mod utils;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-// mod day21;
-// mod day22;
-// mod day23;
-// mod day24;
-// mod day25;
+mod determinism;
+mod json;
+mod options;
+mod timing;
 
 use std::env;
 
-type DayFunction = fn(&[String]) -> (u64, u64);
-static DAY_FUNCTIONS: [DayFunction; 20] = [
-    day1::day1,
-    day2::day2,
-    day3::day3,
-    day4::day4,
-    day5::day5,
-    day6::day6,
-    day7::day7,
-    day8::day8,
-    day9::day9,
-    day10::day10,
-    day11::day11,
-    day12::day12,
-    day13::day13,
-    day14::day14,
-    day15::day15,
-    day16::day16,
-    day17::day17,
-    day18::day18,
-    day19::day19,
-    day20::day20,
-    // day21::day21,
-    // day22::day22,
-    // day23::day23,
-    // day24::day24,
-    // day25::day25,
-];
+use advent_of_code_2021::{utils, DAY_FUNCTIONS, DAY_PARAMS};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let min_day: usize;
-    let max_day: usize;
-    if args.len() < 2 {
-        min_day = 1;
-        max_day = DAY_FUNCTIONS.len();
-    } else {
-        min_day = args[1].parse::<usize>().expect("Please provide the day number as an integer.");
-        if (min_day < 1) || (min_day > DAY_FUNCTIONS.len()) {
-            panic!("Invalid day specified.");
+    let options = options::Options::parse(&args, DAY_FUNCTIONS.len());
+    if options.explain {
+        utils::explain::enable();
+    }
+    if options.audit {
+        utils::audit::enable();
+    }
+    if let Some(dir) = &options.svg {
+        utils::svg::configure(dir.clone());
+    }
+    if let Some(dir) = &options.dot {
+        utils::graph::configure(dir.clone());
+    }
+    for param_override in &options.params {
+        let day_params = DAY_PARAMS[param_override.day - 1];
+        let param = day_params.iter().find(|param| param.name() == param_override.name).unwrap_or_else(|| {
+            let available = day_params.iter().map(|param| format!("{} ({})", param.name(), param.description())).collect::<Vec<_>>();
+            panic!("Day {} has no parameter {:?}; it has: {}", param_override.day, param_override.name, if available.is_empty() { "none".to_string() } else { available.join(", ") });
+        });
+        if let Err(message) = param.set(&param_override.value) {
+            panic!("Invalid --param: {}", message);
         }
-        max_day = min_day;
     }
 
-    let mut total_elapsed = 0u128;
-    for day in min_day..=max_day {
+    let mut timings = timing::Timings::default();
+    let mut failed = false;
+    let mut reports: Vec<json::DayReport> = Vec::new();
+    for day in options.min_day..=options.max_day {
         println!("Day {}", day);
-        let input_lines = utils::load_inputs(day);
+        let params = DAY_PARAMS[day - 1].iter().map(|param| (param.name(), param.to_json())).collect();
+        let load_start_time = std::time::Instant::now();
+        let input = utils::load_inputs(day);
+        let load_elapsed = load_start_time.elapsed().as_micros();
+        if let Err(mismatch) = utils::signature::check(day, &input) {
+            println!("Skipping: {}", mismatch);
+            println!("----------");
+            reports.push(json::DayReport { day, outcome: json::Outcome::Skipped(mismatch.to_string()), params });
+            failed = true;
+            continue;
+        }
+        if let Some(output) = &options.visualize {
+            utils::visualize::start(output, day);
+        }
         let start_time = std::time::Instant::now();
-        let (part1, part2) = DAY_FUNCTIONS[day - 1](&input_lines);
+        let result = utils::audit::run(day, || DAY_FUNCTIONS[day - 1](&input));
         let elapsed = start_time.elapsed().as_micros();
-        println!("Part 1: {}\nPart 2: {}", part1, part2);
-        println!("{}.{:03}ms", elapsed / 1000, elapsed % 1000);
-        total_elapsed += elapsed;
+        utils::visualize::finish();
+        let result = match result {
+            Ok(result) => result,
+            Err(overflow) => {
+                println!("Arithmetic overflow: {}", overflow);
+                println!("----------");
+                reports.push(json::DayReport { day, outcome: json::Outcome::Overflow(overflow.to_string()), params });
+                failed = true;
+                continue;
+            },
+        };
+        let outcome = match &result {
+            Ok((part1, part2)) => {
+                println!("Part 1: {}\nPart 2: {}", part1, part2);
+                println!("{}", timing::format_micros(elapsed));
+                timings.record(timing::DayTiming { day, load: load_elapsed, solve: elapsed });
+                json::Outcome::Solved { part1: part1.clone(), part2: part2.clone(), load: load_elapsed, solve: elapsed }
+            },
+            Err(error) => {
+                println!("Invalid input: {}", error);
+                failed = true;
+                json::Outcome::Invalid(error.to_string())
+            },
+        };
+        reports.push(json::DayReport { day, outcome, params });
+        if options.determinism_check {
+            let differences = determinism::check(DAY_FUNCTIONS[day - 1], &input, &result);
+            if !differences.is_empty() {
+                println!("Nondeterministic: {}", differences.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "));
+                failed = true;
+            }
+        }
         println!("----------");
     }
-    println!("**************");
-    println!("Cumulative total: {}.{:03}ms", total_elapsed / 1000, total_elapsed % 1000);
+    if let Some(path) = &options.json {
+        json::write(path, &reports);
+    }
+    if !timings.report(&options.budget) || failed {
+        std::process::exit(1);
+    }
 }
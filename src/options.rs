@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use crate::timing::Budget;
+use advent_of_code_2021::utils::visualize;
+
+/// A `--param dayN.name=value` override.
+pub struct ParamOverride {
+    pub day: usize,
+    pub name: String,
+    pub value: String,
+}
+
+impl ParamOverride {
+    fn parse(spec: &str, num_days: usize) -> Self {
+        let usage = "--param requires a value like day6.part2_days=1000";
+        let (name, value) = spec.split_once('=').expect(usage);
+        let (day, name) = name.split_once('.').expect(usage);
+        let day = day.strip_prefix("day").and_then(|day| day.parse::<usize>().ok()).expect(usage);
+        if (day < 1) || (day > num_days) {
+            panic!("Invalid day specified in --param.");
+        }
+        Self { day, name: name.to_string(), value: value.to_string() }
+    }
+}
+
+pub struct Options {
+    pub min_day: usize,
+    pub max_day: usize,
+    pub budget: Budget,
+    pub visualize: Option<visualize::Output>,
+    pub svg: Option<PathBuf>,
+    pub dot: Option<PathBuf>,
+    pub explain: bool,
+    pub audit: bool,
+    pub determinism_check: bool,
+    pub params: Vec<ParamOverride>,
+    pub json: Option<PathBuf>,
+}
+
+impl Options {
+    pub fn parse(args: &[String], num_days: usize) -> Self {
+        let mut day: Option<usize> = None;
+        let mut budget = Budget::default();
+        let mut visualize: Option<visualize::Output> = None;
+        let mut svg: Option<PathBuf> = None;
+        let mut dot: Option<PathBuf> = None;
+        let mut explain = false;
+        let mut audit = false;
+        let mut determinism_check = false;
+        let mut params: Vec<ParamOverride> = Vec::new();
+        let mut json: Option<PathBuf> = None;
+
+        let mut args_iter = args.iter().skip(1);
+        while let Some(arg) = args_iter.next() {
+            match arg.as_str() {
+                "--budget" => budget.add(args_iter.next().expect("--budget requires a value, e.g. --budget 1000 or --budget day15=200"), num_days),
+                "--visualize" => visualize = Some(visualize::Output::parse(args_iter.next().expect("--visualize requires a format, e.g. --visualize gif or --visualize png:frames"))),
+                "--svg" => svg = Some(PathBuf::from(args_iter.next().expect("--svg requires an output directory"))),
+                "--dot" => dot = Some(PathBuf::from(args_iter.next().expect("--dot requires an output directory"))),
+                "--explain" => explain = true,
+                "--audit" => audit = true,
+                "--determinism-check" => determinism_check = true,
+                "--param" => params.push(ParamOverride::parse(args_iter.next().expect("--param requires a value like day6.part2_days=1000"), num_days)),
+                "--json" => json = Some(PathBuf::from(args_iter.next().expect("--json requires an output file"))),
+                _ => {
+                    let parsed_day = arg.parse::<usize>().expect("Please provide the day number as an integer.");
+                    if (parsed_day < 1) || (parsed_day > num_days) {
+                        panic!("Invalid day specified.");
+                    }
+                    day = Some(parsed_day);
+                }
+            }
+        }
+
+        // The extra runs would repeat all of the output.
+        if determinism_check && (visualize.is_some() || svg.is_some() || dot.is_some() || explain) {
+            panic!("--determinism-check can't be combined with --visualize, --svg, --dot or --explain");
+        }
+
+        let (min_day, max_day) = match day {
+            Some(day) => (day, day),
+            None => (1, num_days),
+        };
+        Self { min_day, max_day, budget, visualize, svg, dot, explain, audit, determinism_check, params, json }
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+pub fn format_micros(micros: u128) -> String {
+    format!("{}.{:03}ms", micros / 1000, micros % 1000)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Load,
+    Solve,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load => write!(f, "load"),
+            Self::Solve => write!(f, "solve"),
+        }
+    }
+}
+
+pub struct DayTiming {
+    pub day: usize,
+    pub load: u128,
+    pub solve: u128,
+}
+
+impl DayTiming {
+    pub fn total(&self) -> u128 {
+        self.load + self.solve
+    }
+
+    fn phase(&self, phase: Phase) -> u128 {
+        match phase {
+            Phase::Load => self.load,
+            Phase::Solve => self.solve,
+        }
+    }
+}
+
+/// Time limits, in microseconds, for the whole run and for individual days,
+/// each covering loading as well as solving.
+#[derive(Default)]
+pub struct Budget {
+    total: Option<u128>,
+    every_day: Option<u128>,
+    days: HashMap<usize, u128>,
+}
+
+impl Budget {
+    /// Adds a limit from a command-line specification, given in milliseconds:
+    /// `1000` or `total=1000` for the whole run, `day=50` for every day, and
+    /// `day15=200` for a single day.
+    pub fn add(&mut self, spec: &str, num_days: usize) {
+        let (target, value) = spec.split_once('=').unwrap_or(("total", spec));
+        let millis = value.parse::<f64>().expect("Budget must be a number of milliseconds");
+        if !(millis.is_finite() && millis >= 0.0) {
+            panic!("Budget must be a non-negative number of milliseconds, not {}", value);
+        }
+        let micros = (millis * 1000.0) as u128;
+        match target {
+            "total" => self.total = Some(micros),
+            "day" => self.every_day = Some(micros),
+            _ => {
+                let day = target.strip_prefix("day").and_then(|day| day.parse::<usize>().ok()).expect("Budget target must be total, day or dayN");
+                if (day < 1) || (day > num_days) {
+                    panic!("Invalid day specified in --budget.");
+                }
+                self.days.insert(day, micros);
+            }
+        }
+    }
+
+    fn for_day(&self, day: usize) -> Option<u128> {
+        self.days.get(&day).copied().or(self.every_day)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.total.is_none() && self.every_day.is_none() && self.days.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct Timings {
+    days: Vec<DayTiming>,
+}
+
+impl Timings {
+    pub fn record(&mut self, timing: DayTiming) {
+        self.days.push(timing);
+    }
+
+    pub fn total(&self) -> u128 {
+        self.days.iter().map(DayTiming::total).sum()
+    }
+
+    pub fn solve_total(&self) -> u128 {
+        self.days.iter().map(|timing| timing.solve).sum()
+    }
+
+    /// Prints the summary table and checks it against the budget, returning
+    /// whether the run stayed within it.
+    pub fn report(&self, budget: &Budget) -> bool {
+        let total = self.total();
+        let slowest = self.days.iter()
+            .flat_map(|timing| [Phase::Load, Phase::Solve].iter().map(move |&phase| (timing.day, phase, timing.phase(phase))))
+            .max_by_key(|&(_, _, elapsed)| elapsed);
+
+        println!("**************");
+        println!("{:>3}  {:>12}  {:>12}  {:>12}  {:>6}", "Day", "Load", "Solve", "Total", "Share");
+        let mut sorted: Vec<&DayTiming> = self.days.iter().collect();
+        sorted.sort_by_key(|timing| std::cmp::Reverse(timing.total()));
+        for timing in sorted {
+            let phase_cell = |phase: Phase| {
+                let marker = if slowest.map(|(day, slowest_phase, _)| (day, slowest_phase)) == Some((timing.day, phase)) { "*" } else { " " };
+                format!("{:>12}{}", format_micros(timing.phase(phase)), marker)
+            };
+            let share = if total == 0 { 0.0 } else { timing.total() as f64 * 100.0 / total as f64 };
+            println!("{:>3}  {} {} {:>12}  {:>5.1}%", timing.day, phase_cell(Phase::Load), phase_cell(Phase::Solve), format_micros(timing.total()), share);
+        }
+        println!("Cumulative total: {}", format_micros(self.solve_total()));
+        println!("Cumulative total including loading: {}", format_micros(total));
+        if let Some((day, phase, elapsed)) = slowest {
+            println!("* Slowest phase: day {} {} ({})", day, phase, format_micros(elapsed));
+        }
+
+        if budget.is_empty() {
+            return true;
+        }
+        let mut within_budget = true;
+        for timing in self.days.iter() {
+            if let Some(limit) = budget.for_day(timing.day) {
+                if timing.total() > limit {
+                    println!("Budget exceeded: day {} took {} including loading, budget is {}", timing.day, format_micros(timing.total()), format_micros(limit));
+                    within_budget = false;
+                }
+            }
+        }
+        if let Some(limit) = budget.total {
+            if total > limit {
+                println!("Budget exceeded: total {} including loading, budget is {}", format_micros(total), format_micros(limit));
+                within_budget = false;
+            }
+        }
+        if within_budget {
+            println!("Within budget");
+        }
+        within_budget
+    }
+}
@@ -1,10 +1,37 @@
-static INPUTS_DIR: &str = "inputs";
+pub mod answer;
+pub mod audit;
+pub mod bits;
+pub mod cuboid;
+pub mod cycle;
+pub mod explain;
+pub mod graph;
+pub mod grid;
+pub mod infinite_grid;
+pub mod input;
+pub mod matrix;
+pub mod memo;
+pub mod ocr;
+pub mod params;
+pub mod parse;
+pub mod search;
+pub mod signature;
+pub mod simulation;
+pub mod small_set;
+pub mod svg;
+pub mod union_find;
+pub mod vector;
+pub mod visualize;
 
-pub fn load_inputs(day: usize) -> Vec<String> {
-    let input = std::fs::read_to_string(format!("{}/{}", INPUTS_DIR, day)).expect("Can't open/read input file");
-    input.lines().map(std::string::ToString::to_string).collect()
-}
+use std::path::Path;
+
+use input::Input;
+
+static INPUTS_DIR: &str = "inputs";
 
-pub fn group_lines_split_by_empty_line(lines: &[String]) -> Vec<&[String]> {
-    lines.split(|line| line.is_empty()).collect()
+pub fn load_inputs(day: usize) -> Input {
+    let input = Input::read(&Path::new(INPUTS_DIR).join(day.to_string())).expect("Can't open/read input file");
+    if !input.changes().is_empty() {
+        println!("Warning: input for day {} was normalised ({})", day, input.changes().join(", "));
+    }
+    input
 }
@@ -0,0 +1,47 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use num_bigint::BigUint;
+
+/// One part's answer. Most fit in a `u64`, but some days' answers can grow
+/// without limit, and those are kept exactly rather than wrapping, and some
+/// aren't numbers at all.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Answer {
+    Number(u64),
+    /// Only ever holds values too big for `Number`, so that equal answers
+    /// always compare equal.
+    Big(BigUint),
+    Text(String),
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Answer::Number(value)
+    }
+}
+
+impl From<BigUint> for Answer {
+    fn from(value: BigUint) -> Self {
+        match u64::try_from(&value) {
+            Ok(value) => Answer::Number(value),
+            Err(_) => Answer::Big(value),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(value) => write!(f, "{}", value),
+            Answer::Big(value) => write!(f, "{}", value),
+            Answer::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe, Location};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static DAY: AtomicUsize = AtomicUsize::new(0);
+
+/// Turns on checked arithmetic for the rest of the run.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Arithmetic that overflowed while auditing, and where.
+#[derive(Clone, Debug)]
+pub struct Overflow {
+    pub day: usize,
+    pub location: &'static Location<'static>,
+    pub operation: String,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}: {} overflowed at {}", self.day, self.operation, self.location)
+    }
+}
+
+/// Runs a day's solution, turning any overflow the audited arithmetic finds
+/// into an error rather than a panic. Other panics carry on as normal.
+pub fn run<R>(day: usize, solve: impl FnOnce() -> R) -> Result<R, Overflow> {
+    DAY.store(day, Ordering::Relaxed);
+    panic::catch_unwind(AssertUnwindSafe(solve)).map_err(|payload| match payload.downcast::<Overflow>() {
+        Ok(overflow) => *overflow,
+        Err(payload) => panic::resume_unwind(payload),
+    })
+}
+
+fn report(location: &'static Location<'static>, operation: String) -> ! {
+    let overflow = Overflow { day: DAY.load(Ordering::Relaxed), location, operation };
+    // Unwinding without panicking skips the panic hook, so the report isn't
+    // preceded by an anonymous panic message.
+    panic::resume_unwind(Box::new(overflow))
+}
+
+/// The integer types audited arithmetic works on.
+pub trait Integer: Copy + fmt::Display + std::ops::Add<Output = Self> + std::ops::Sub<Output = Self> + std::ops::Mul<Output = Self> {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($type:ty),*) => {
+        $(impl Integer for $type {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$type>::checked_add(self, other)
+            }
+
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$type>::checked_sub(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$type>::checked_mul(self, other)
+            }
+        })*
+    };
+}
+
+integer!(u8, u32, u64, usize, i32, i64);
+
+/// `a + b`, checked when auditing.
+#[track_caller]
+pub fn add<T: Integer>(a: T, b: T) -> T {
+    if !enabled() {
+        return a + b;
+    }
+    // Closures don't inherit `#[track_caller]`, so get the location here.
+    let location = Location::caller();
+    a.checked_add(b).unwrap_or_else(|| report(location, format!("{} + {}", a, b)))
+}
+
+/// `a - b`, checked when auditing.
+#[track_caller]
+pub fn sub<T: Integer>(a: T, b: T) -> T {
+    if !enabled() {
+        return a - b;
+    }
+    let location = Location::caller();
+    a.checked_sub(b).unwrap_or_else(|| report(location, format!("{} - {}", a, b)))
+}
+
+/// `a * b`, checked when auditing.
+#[track_caller]
+pub fn mul<T: Integer>(a: T, b: T) -> T {
+    if !enabled() {
+        return a * b;
+    }
+    let location = Location::caller();
+    a.checked_mul(b).unwrap_or_else(|| report(location, format!("{} * {}", a, b)))
+}
+
+/// The checked half of `cast!`.
+#[track_caller]
+pub fn checked_cast<T: Copy + fmt::Display, U: TryFrom<T>>(value: T) -> U {
+    let location = Location::caller();
+    U::try_from(value).unwrap_or_else(|_| report(location, format!("{} as {}", value, std::any::type_name::<U>())))
+}
+
+/// `value as Type`, checked when auditing: `cast!(x => usize)`.
+macro_rules! cast {
+    ($value:expr => $type:ty) => {{
+        let value = $value;
+        if $crate::utils::audit::enabled() {
+            $crate::utils::audit::checked_cast::<_, $type>(value)
+        } else {
+            value as $type
+        }
+    }};
+}
+
+pub(crate) use cast;
@@ -0,0 +1,285 @@
+use std::fmt::Write as _;
+use std::io::{self, Read};
+
+/// A character in hex text that isn't a hex digit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidHexDigit {
+    /// Which character it was, counting from 0.
+    pub index: usize,
+    pub found: char,
+}
+
+/// Reads fields of any number of bits, most significant bit first, from a
+/// stream of bytes.
+pub struct BitReader {
+    bytes: Vec<u8>,
+    /// How many bits the stream holds. Hex text with an odd number of digits
+    /// doesn't fill its last byte.
+    length: usize,
+    position: usize,
+}
+
+impl BitReader {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self { bytes: bytes.to_vec(), length: bytes.len() * 8, position: 0 }
+    }
+
+    /// Reads hex text, each digit of which holds four bits.
+    pub fn from_hex(text: &str) -> Result<Self, InvalidHexDigit> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(text.len().div_ceil(2));
+        for (index, c) in text.chars().enumerate() {
+            let digit = c.to_digit(16).ok_or(InvalidHexDigit { index, found: c })? as u8;
+            if index % 2 == 0 {
+                bytes.push(digit << 4);
+            } else {
+                *bytes.last_mut().unwrap() |= digit;
+            }
+        }
+        Ok(Self { length: text.chars().count() * 4, bytes, position: 0 })
+    }
+
+    /// Reads everything `reader` has to give.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Ok(Self { length: bytes.len() * 8, bytes, position: 0 })
+    }
+
+    /// How many bits the stream holds in total.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// How many bits have been read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.length - self.position
+    }
+
+    /// Reads a field of up to 64 bits, or returns `None`, leaving the
+    /// position alone, if there aren't that many bits left.
+    pub fn read(&mut self, num_bits: usize) -> Option<u64> {
+        assert!(num_bits <= 64, "can't read {} bits into a u64", num_bits);
+        self.read_u128(num_bits).map(|value| value as u64)
+    }
+
+    /// Reads a field of up to 128 bits.
+    pub fn read_u128(&mut self, num_bits: usize) -> Option<u128> {
+        assert!(num_bits <= 128, "can't read {} bits into a u128", num_bits);
+        if num_bits > self.remaining() {
+            return None;
+        }
+
+        let mut value = 0u128;
+        let mut bits_wanted = num_bits;
+        while bits_wanted > 0 {
+            // Take as much as we want from the current byte.
+            let offset = self.position % 8;
+            let available = 8 - offset;
+            let taken = available.min(bits_wanted);
+            let byte = self.bytes[self.position / 8] & (0xff >> offset);
+            value = (value << taken) | (byte >> (available - taken)) as u128;
+            self.position += taken;
+            bits_wanted -= taken;
+        }
+        Some(value)
+    }
+
+    /// Reads a field of any width as big-endian bytes, with the field
+    /// right-aligned in them, so a 12-bit field comes back as two bytes whose
+    /// top four bits are zero.
+    pub fn read_bytes(&mut self, num_bits: usize) -> Option<Vec<u8>> {
+        if num_bits > self.remaining() {
+            return None;
+        }
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(num_bits.div_ceil(8));
+        let leading_bits = num_bits % 8;
+        if leading_bits > 0 {
+            bytes.push(self.read(leading_bits).unwrap() as u8);
+        }
+        for _ in 0..num_bits / 8 {
+            bytes.push(self.read(8).unwrap() as u8);
+        }
+        Some(bytes)
+    }
+}
+
+/// Builds a stream of bits, most significant bit first, the inverse of
+/// `BitReader`.
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    length: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many bits have been written.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Writes the low `num_bits` bits of `value`.
+    pub fn write(&mut self, value: u64, num_bits: usize) {
+        assert!(num_bits <= 64, "can't write {} bits from a u64", num_bits);
+        self.write_u128(value as u128, num_bits);
+    }
+
+    /// Writes the low `num_bits` bits of `value`.
+    pub fn write_u128(&mut self, value: u128, num_bits: usize) {
+        assert!(num_bits <= 128, "can't write {} bits from a u128", num_bits);
+        let mut bits_left = num_bits;
+        while bits_left > 0 {
+            // Fill up the current byte, starting a new one if it's full.
+            let offset = self.length % 8;
+            if offset == 0 {
+                self.bytes.push(0);
+            }
+            let space = 8 - offset;
+            let taken = space.min(bits_left);
+            let bits = ((value >> (bits_left - taken)) as u8) & (0xff >> (8 - taken));
+            *self.bytes.last_mut().unwrap() |= bits << (space - taken);
+            self.length += taken;
+            bits_left -= taken;
+        }
+    }
+
+    /// Writes a field of `num_bits` bits held right-aligned in big-endian
+    /// `bytes`, as `BitReader::read_bytes` returns them.
+    pub fn write_bytes(&mut self, bytes: &[u8], num_bits: usize) {
+        assert!(num_bits <= bytes.len() * 8, "{} bytes don't hold {} bits", bytes.len(), num_bits);
+        let mut bits_left = num_bits;
+        for &byte in &bytes[bytes.len() - num_bits.div_ceil(8)..] {
+            let taken = if bits_left.is_multiple_of(8) { 8 } else { bits_left % 8 };
+            self.write(byte as u64, taken);
+            bits_left -= taken;
+        }
+    }
+
+    /// The bits written so far, with the last byte padded with zeros.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// The bits written so far as upper-case hex, with the last digit padded
+    /// with zeros.
+    pub fn to_hex(&self) -> String {
+        let mut hex = String::with_capacity(self.length.div_ceil(4));
+        for byte in &self.bytes {
+            write!(hex, "{:02X}", byte).unwrap();
+        }
+        hex.truncate(self.length.div_ceil(4));
+        hex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fixed sequence of pseudo-random numbers, so that failures repeat.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// A value of exactly `num_bits` bits or fewer.
+        fn field(&mut self, num_bits: usize) -> u128 {
+            let value = (self.next() as u128) << 64 | self.next() as u128;
+            if num_bits == 128 { value } else { value & ((1 << num_bits) - 1) }
+        }
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        let mut rng = XorShift(0x2545f4914f6cdd1d);
+        // Every width, at every offset within a byte.
+        let fields: Vec<(u128, usize)> = (0..8).flat_map(|_| 1..=128).map(|num_bits| (rng.field(num_bits), num_bits)).collect();
+        let mut writer = BitWriter::new();
+        for &(value, num_bits) in &fields {
+            if num_bits <= 64 {
+                writer.write(value as u64, num_bits);
+            } else {
+                writer.write_u128(value, num_bits);
+            }
+        }
+        let total: usize = fields.iter().map(|(_, num_bits)| num_bits).sum();
+        assert_eq!(writer.len(), total);
+
+        let hex = writer.to_hex();
+        for mut reader in [BitReader::from_hex(&hex).unwrap(), BitReader::from_bytes(&writer.into_bytes())] {
+            for &(value, num_bits) in &fields {
+                let read = if num_bits <= 64 { reader.read(num_bits).map(u128::from) } else { reader.read_u128(num_bits) };
+                assert_eq!(read, Some(value), "{}-bit field at bit {}", num_bits, reader.position());
+            }
+            assert_eq!(reader.position(), total);
+            // Padding at most fills out the last byte.
+            assert!(reader.remaining() < 8);
+            assert_eq!(reader.read(8), None);
+            assert_eq!(reader.position(), total);
+        }
+    }
+
+    #[test]
+    fn odd_length_hex() {
+        let mut reader = BitReader::from_hex("a1F").unwrap();
+        assert_eq!(reader.len(), 12);
+        assert_eq!(reader.read(13), None);
+        assert_eq!(reader.read(3), Some(0b101));
+        assert_eq!(reader.read(9), Some(0b0_0001_1111));
+        assert!(reader.read(1).is_none());
+        assert_eq!(BitReader::from_hex("12G4").err(), Some(InvalidHexDigit { index: 2, found: 'G' }));
+        assert!(BitReader::from_hex("").unwrap().is_empty());
+
+        let mut writer = BitWriter::new();
+        writer.write(0xA1F, 12);
+        assert_eq!(writer.to_hex(), "A1F");
+        writer.write(1, 1);
+        assert_eq!(writer.to_hex(), "A1F8");
+        assert_eq!(writer.into_bytes(), vec![0xA1, 0xF8]);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut rng = XorShift(0x9e3779b97f4a7c15);
+        let mut writer = BitWriter::new();
+        let mut fields: Vec<(Vec<u8>, usize)> = Vec::new();
+        for num_bits in 0..=40usize {
+            let mut bytes: Vec<u8> = (0..num_bits.div_ceil(8)).map(|_| rng.next() as u8).collect();
+            if num_bits % 8 > 0 {
+                bytes[0] &= (1 << (num_bits % 8)) - 1;
+            }
+            writer.write_bytes(&bytes, num_bits);
+            fields.push((bytes, num_bits));
+        }
+        // Extra leading bytes are ignored.
+        writer.write_bytes(&[0xff, 0x0a, 0xbc], 12);
+        fields.push((vec![0x0a, 0xbc], 12));
+
+        let mut reader = BitReader::from_reader(writer.into_bytes().as_slice()).unwrap();
+        for (bytes, num_bits) in fields {
+            assert_eq!(reader.read_bytes(num_bits), Some(bytes), "{}-bit field", num_bits);
+        }
+        assert_eq!(reader.read_bytes(reader.remaining() + 1), None);
+    }
+}
@@ -0,0 +1,293 @@
+use std::iter::FromIterator;
+use std::ops::{Range, RangeInclusive};
+
+/// A range of integers, from `start` up to but not including `end`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub const fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn len(&self) -> u64 {
+        if self.is_empty() { 0 } else { self.end.abs_diff(self.start) }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// The values in both intervals, if there are any.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let intersection = Self::new(self.start.max(other.start), self.end.min(other.end));
+        if intersection.is_empty() { None } else { Some(intersection) }
+    }
+
+    /// The values in this interval but not in `other`, as at most two
+    /// intervals: the part below `other` and the part above it.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(overlap) = self.intersection(other) else {
+            return if self.is_empty() { Vec::new() } else { vec![*self] };
+        };
+        IntoIterator::into_iter([Self::new(self.start, overlap.start), Self::new(overlap.end, self.end)])
+            .filter(|piece| !piece.is_empty())
+            .collect()
+    }
+}
+
+impl From<Range<i64>> for Interval {
+    fn from(range: Range<i64>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<RangeInclusive<i64>> for Interval {
+    fn from(range: RangeInclusive<i64>) -> Self {
+        Self::new(*range.start(), *range.end() + 1)
+    }
+}
+
+/// An axis-aligned box in `N` dimensions: an interval along each axis.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Cuboid<const N: usize> {
+    pub axes: [Interval; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub const fn new(axes: [Interval; N]) -> Self {
+        Self { axes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(Interval::is_empty)
+    }
+
+    /// How many integer points the cuboid contains. Panics if that doesn't
+    /// fit in a `u64`.
+    pub fn volume(&self) -> u64 {
+        self.axes.iter().try_fold(1u64, |volume, axis| volume.checked_mul(axis.len())).expect("Cuboid volume overflowed")
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        self.axes.iter().zip(point).all(|(axis, value)| axis.contains(value))
+    }
+
+    /// The points in both cuboids, if there are any.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut axes = self.axes;
+        for (axis, other_axis) in axes.iter_mut().zip(&other.axes) {
+            *axis = axis.intersection(other_axis)?;
+        }
+        Some(Self { axes })
+    }
+
+    /// The points in this cuboid but not in `other`, as at most `2 * N`
+    /// disjoint cuboids.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(overlap) = self.intersection(other) else {
+            return if self.is_empty() { Vec::new() } else { vec![*self] };
+        };
+
+        // Slice off the parts outside the overlap one axis at a time,
+        // narrowing what's left to the overlap along each axis as we go.
+        let mut pieces: Vec<Self> = Vec::with_capacity(2 * N);
+        let mut remainder = *self;
+        for axis in 0..N {
+            for interval in remainder.axes[axis].subtract(&overlap.axes[axis]) {
+                let mut piece = remainder;
+                piece.axes[axis] = interval;
+                pieces.push(piece);
+            }
+            remainder.axes[axis] = overlap.axes[axis];
+        }
+        pieces
+    }
+}
+
+/// A set of points, held as disjoint cuboids.
+#[derive(Clone, Debug)]
+pub struct CuboidSet<const N: usize> {
+    cuboids: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> Default for CuboidSet<N> {
+    fn default() -> Self {
+        Self { cuboids: Vec::new() }
+    }
+}
+
+impl<const N: usize> CuboidSet<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The disjoint cuboids making up the set, in no particular order.
+    pub fn cuboids(&self) -> &[Cuboid<N>] {
+        &self.cuboids
+    }
+
+    /// Adds every point in `cuboid` to the set.
+    pub fn insert(&mut self, cuboid: Cuboid<N>) {
+        self.remove(&cuboid);
+        if !cuboid.is_empty() {
+            self.cuboids.push(cuboid);
+        }
+    }
+
+    /// Removes every point in `cuboid` from the set.
+    pub fn remove(&mut self, cuboid: &Cuboid<N>) {
+        self.cuboids = self.cuboids.iter().flat_map(|existing| existing.subtract(cuboid)).collect();
+    }
+
+    /// How many points are in the set.
+    pub fn volume(&self) -> u64 {
+        self.cuboids.iter().map(Cuboid::volume).sum()
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        self.cuboids.iter().any(|cuboid| cuboid.contains(point))
+    }
+
+    /// The points in both this set and `cuboid`.
+    pub fn intersection(&self, cuboid: &Cuboid<N>) -> Self {
+        Self { cuboids: self.cuboids.iter().filter_map(|existing| existing.intersection(cuboid)).collect() }
+    }
+}
+
+impl<const N: usize> FromIterator<Cuboid<N>> for CuboidSet<N> {
+    /// The union of the cuboids, split into disjoint pieces.
+    fn from_iter<I: IntoIterator<Item = Cuboid<N>>>(cuboids: I) -> Self {
+        let mut set = Self::new();
+        for cuboid in cuboids {
+            set.insert(cuboid);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// Every cuboid lies within this space on each axis, so a set of voxels
+    /// covering it is a brute-force model of the cuboid arithmetic.
+    fn space() -> Range<i64> {
+        -5..6
+    }
+
+    /// A fixed sequence of pseudo-random numbers, so that failures repeat.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn coordinate(&mut self) -> i64 {
+            (self.next() % 9) as i64 - 4
+        }
+
+        fn cuboid(&mut self) -> Cuboid<3> {
+            // Ends may come before starts, making empty cuboids, which must
+            // work too.
+            let mut axis = || Interval::new(self.coordinate(), self.coordinate());
+            Cuboid::new([axis(), axis(), axis()])
+        }
+    }
+
+    fn voxels(cuboid: &Cuboid<3>) -> HashSet<[i64; 3]> {
+        let mut voxels = HashSet::new();
+        for x in space() {
+            for y in space() {
+                for z in space() {
+                    if cuboid.contains([x, y, z]) {
+                        voxels.insert([x, y, z]);
+                    }
+                }
+            }
+        }
+        voxels
+    }
+
+    /// The voxels in some pieces, checking that they don't overlap and that
+    /// their volumes add up.
+    fn pieces_voxels(pieces: &[Cuboid<3>]) -> HashSet<[i64; 3]> {
+        let voxels: Vec<[i64; 3]> = pieces.iter().flat_map(voxels).collect();
+        let unique: HashSet<[i64; 3]> = voxels.iter().copied().collect();
+        assert_eq!(voxels.len(), unique.len(), "pieces overlap");
+        assert_eq!(pieces.iter().map(Cuboid::volume).sum::<u64>(), unique.len() as u64);
+        unique
+    }
+
+    #[test]
+    fn intervals_match_integers() {
+        let mut random = XorShift(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..1000 {
+            let a = Interval::new(random.coordinate(), random.coordinate());
+            let b = Interval::new(random.coordinate(), random.coordinate());
+            let in_a: HashSet<i64> = space().filter(|&value| a.contains(value)).collect();
+            let in_b: HashSet<i64> = space().filter(|&value| b.contains(value)).collect();
+            assert_eq!(a.len(), in_a.len() as u64);
+
+            let intersection: HashSet<i64> = a.intersection(&b).into_iter().flat_map(|interval| space().filter(move |&value| interval.contains(value))).collect();
+            assert_eq!(intersection, &in_a & &in_b);
+            let difference = a.subtract(&b);
+            assert_eq!(difference.iter().map(Interval::len).sum::<u64>(), (&in_a - &in_b).len() as u64);
+            assert!(space().all(|value| difference.iter().any(|interval| interval.contains(value)) == (in_a.contains(&value) && !in_b.contains(&value))));
+        }
+    }
+
+    #[test]
+    fn cuboids_match_voxels() {
+        let mut random = XorShift(0x2545_f491_4f6c_dd1d);
+        let mut previous = Cuboid::new([Interval::new(0, 0); 3]);
+        for _ in 0..2000 {
+            let cuboid = random.cuboid();
+            let cuboid_voxels = voxels(&cuboid);
+            let previous_voxels = voxels(&previous);
+            assert_eq!(cuboid.volume(), cuboid_voxels.len() as u64);
+
+            let intersection: Vec<Cuboid<3>> = cuboid.intersection(&previous).into_iter().collect();
+            assert_eq!(pieces_voxels(&intersection), &cuboid_voxels & &previous_voxels);
+            assert_eq!(pieces_voxels(&cuboid.subtract(&previous)), &cuboid_voxels - &previous_voxels);
+            previous = cuboid;
+        }
+    }
+
+    #[test]
+    fn cuboid_sets_match_voxels() {
+        for seed in 1..=20u64 {
+            let mut random = XorShift(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let mut set: CuboidSet<3> = CuboidSet::new();
+            let mut expected: HashSet<[i64; 3]> = HashSet::new();
+            for _ in 0..50 {
+                let cuboid = random.cuboid();
+                let cuboid_voxels = voxels(&cuboid);
+                if random.next().is_multiple_of(3) {
+                    set.remove(&cuboid);
+                    expected.retain(|voxel| !cuboid_voxels.contains(voxel));
+                } else {
+                    set.insert(cuboid);
+                    expected.extend(&cuboid_voxels);
+                }
+                assert_eq!(pieces_voxels(set.cuboids()), expected);
+                assert_eq!(set.volume(), expected.len() as u64);
+                assert!(space().all(|x| set.contains([x, 0, 0]) == expected.contains(&[x, 0, 0])));
+                assert_eq!(pieces_voxels(set.intersection(&cuboid).cuboids()), &expected & &cuboid_voxels);
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Where a deterministic simulation starts repeating itself: the state after
+/// `start` steps is the first that comes round again, `length` steps later.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest step with the same state as after `step` steps, which is
+    /// always less than `start + length`.
+    pub fn equivalent_step(&self, step: u64) -> usize {
+        if step < self.start as u64 {
+            step as usize
+        } else {
+            self.start + ((step - self.start as u64) % self.length as u64) as usize
+        }
+    }
+}
+
+/// Finds the cycle with Floyd's tortoise and hare, keeping only a couple of
+/// states at a time. `step` must be a pure function of the state, and the
+/// states must eventually repeat, or this never returns.
+pub fn floyd<S: Clone + PartialEq>(initial: &S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // The hare moves twice as fast, so once both are in the cycle it catches
+    // the tortoise up.
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let halfway = step(&hare);
+        hare = step(&halfway);
+    }
+
+    // The hare is now a whole number of cycles ahead of the tortoise, so
+    // moving them at the same speed they meet at the start of the cycle.
+    let mut start = 0usize;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1usize;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Finds the cycle with Brent's algorithm, which needs fewer steps than
+/// Floyd's and has the same requirements.
+pub fn brent<S: Clone + PartialEq>(initial: &S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // Teleport the tortoise to the hare at each power of two, until the hare
+    // finds it within the next power of two steps.
+    let mut power = 1usize;
+    let mut length = 1usize;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Start the hare a cycle ahead, and they meet at the start of the cycle.
+    let mut start = 0usize;
+    tortoise = initial.clone();
+    hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Every state a simulation goes through before it starts repeating itself,
+/// from which the state after any number of steps can be read off.
+pub struct History<S> {
+    /// The initial state, then the state after each step, up to the end of
+    /// the first time round the cycle.
+    pub states: Vec<S>,
+    pub cycle: Cycle,
+}
+
+impl<S> History<S> {
+    pub fn state_at(&self, step: u64) -> &S {
+        &self.states[self.cycle.equivalent_step(step)]
+    }
+
+    /// The total of `value` over the states after each of the first `steps`
+    /// steps, without visiting any step more than once.
+    pub fn sum_over(&self, steps: u64, value: impl Fn(&S) -> u64) -> u64 {
+        let Cycle { start, length } = self.cycle;
+        let values: Vec<u64> = self.states.iter().map(value).collect();
+        let sum_range = |from: usize, to: usize| values[from..to].iter().sum::<u64>();
+        if steps < (start + length) as u64 {
+            return sum_range(1, steps as usize + 1);
+        }
+
+        // Steps up to the end of the first time round the cycle, then whole
+        // cycles, then whatever's left of a cycle.
+        let steps_in_cycle = steps - (start + length - 1) as u64;
+        let whole_cycles = steps_in_cycle / length as u64;
+        let remainder = (steps_in_cycle % length as u64) as usize;
+        sum_range(1, start + length) + whole_cycles * sum_range(start, start + length) + sum_range(start, start + remainder)
+    }
+}
+
+/// Finds the cycle by remembering every state, which costs memory but means
+/// each state is only calculated once and gets kept for the caller. The
+/// states must eventually repeat, or this never returns.
+pub fn history<S: Clone + Eq + Hash>(initial: S, mut step: impl FnMut(&S) -> S) -> History<S> {
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut states: Vec<S> = Vec::new();
+    let mut state = initial;
+    loop {
+        if let Some(&start) = seen.get(&state) {
+            let length = states.len() - start;
+            return History { states, cycle: Cycle { start, length } };
+        }
+        let next = step(&state);
+        seen.insert(state.clone(), states.len());
+        states.push(state);
+        state = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every sequence `x -> (x * x + c) % modulus`, from every starting
+    /// point, which between them have tails and cycles of all sorts of
+    /// lengths, including none and one.
+    fn sequences() -> impl Iterator<Item = (u64, impl Fn(&u64) -> u64)> {
+        (1..40u64).flat_map(|modulus| {
+            (0..4u64).flat_map(move |c| (0..modulus).map(move |initial| (initial, move |&x: &u64| (x * x + c) % modulus)))
+        })
+    }
+
+    #[test]
+    fn floyd_and_brent_match_history() {
+        for (initial, step) in sequences() {
+            let history = history(initial, &step);
+            assert_eq!(history.states.len(), history.cycle.start + history.cycle.length);
+            assert_eq!(floyd(&initial, &step), history.cycle, "floyd from {}", initial);
+            assert_eq!(brent(&initial, &step), history.cycle, "brent from {}", initial);
+        }
+    }
+
+    #[test]
+    fn state_at_matches_stepping() {
+        for (initial, step) in sequences() {
+            let history = history(initial, &step);
+            let mut state = initial;
+            let mut sum = 0u64;
+            for steps in 0..100u64 {
+                assert_eq!(*history.state_at(steps), state, "state after {} steps from {}", steps, initial);
+                assert_eq!(history.sum_over(steps, |&state| state), sum, "sum over {} steps from {}", steps, initial);
+                state = step(&state);
+                sum += state;
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns on narration for the rest of the run.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn write(message: std::fmt::Arguments) {
+    println!("  > {}", message);
+}
+
+/// Narrates a step of a solution when running with `--explain`. Takes the
+/// same arguments as `println!`; when narration is off, they aren't even
+/// evaluated.
+macro_rules! explain {
+    ($($arg:tt)*) => {
+        if $crate::utils::explain::enabled() {
+            $crate::utils::explain::write(format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use explain;
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static OUTPUT_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+pub type NodeId = usize;
+
+/// A graph of named nodes, such as caves. Each name is interned the first
+/// time it's seen, giving it the next `NodeId`, and each node carries some
+/// attributes of type `A` alongside its outgoing edges.
+pub struct Graph<'a, A> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, NodeId>,
+    attributes: Vec<A>,
+    edges: Vec<Vec<NodeId>>,
+}
+
+impl<'a, A> Default for Graph<'a, A> {
+    fn default() -> Self {
+        Self { names: Vec::new(), ids: HashMap::new(), attributes: Vec::new(), edges: Vec::new() }
+    }
+}
+
+impl<'a, A> Graph<'a, A> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ID of the node called `name`, adding it with the attributes
+    /// `make_attributes` gives if it's new.
+    pub fn intern(&mut self, name: &'a str, make_attributes: impl FnOnce(&str) -> A) -> NodeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name);
+        self.ids.insert(name, id);
+        self.attributes.push(make_attributes(name));
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &'a str {
+        self.names[id]
+    }
+
+    pub fn attributes(&self, id: NodeId) -> &A {
+        &self.attributes[id]
+    }
+
+    pub fn attributes_mut(&mut self, id: NodeId) -> &mut A {
+        &mut self.attributes[id]
+    }
+
+    /// Adds an edge from `from` to `to` only.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId) {
+        self.edges[from].push(to);
+    }
+
+    /// Adds edges both ways between `first` and `second`.
+    pub fn connect(&mut self, first: NodeId, second: NodeId) {
+        self.add_edge(first, second);
+        self.add_edge(second, first);
+    }
+
+    /// The nodes `id` has edges to, in the order they were added.
+    pub fn neighbours(&self, id: NodeId) -> &[NodeId] {
+        &self.edges[id]
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        0..self.names.len()
+    }
+
+    /// The graph in Graphviz's DOT language. `node_attributes` gives any DOT
+    /// attributes for each node, e.g. `shape=box`. Pairs of edges going both
+    /// ways are drawn as a single edge with an arrow at each end.
+    pub fn to_dot(&self, node_attributes: impl Fn(&A) -> String) -> String {
+        let mut dot = String::from("digraph {\n");
+        for id in self.ids() {
+            let attributes = node_attributes(&self.attributes[id]);
+            let separator = if attributes.is_empty() { "" } else { ", " };
+            writeln!(dot, "  {} [label={}{}{}];", id, quote(self.names[id]), separator, attributes).unwrap();
+        }
+        for from in self.ids() {
+            for &to in &self.edges[from] {
+                let both_ways = self.edges[to].contains(&from);
+                if !both_ways {
+                    writeln!(dot, "  {} -> {};", from, to).unwrap();
+                } else if from <= to {
+                    writeln!(dot, "  {} -> {} [dir=both];", from, to).unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Enables DOT export, into the given directory.
+pub fn configure(dir: PathBuf) {
+    *OUTPUT_DIR.lock().unwrap() = Some(dir);
+}
+
+/// Whether DOT export is enabled. Days should check this before doing any
+/// work to build a graph just for export.
+pub fn enabled() -> bool {
+    OUTPUT_DIR.lock().unwrap().is_some()
+}
+
+/// Writes DOT text to `<name>.dot` in the output directory.
+pub fn save(name: &str, dot: &str) {
+    if let Some(dir) = OUTPUT_DIR.lock().unwrap().as_ref() {
+        std::fs::create_dir_all(dir).expect("Can't create DOT directory");
+        let path = dir.join(format!("{}.dot", name));
+        std::fs::write(&path, dot).expect("Can't write DOT file");
+        println!("Wrote {}", path.display());
+    }
+}
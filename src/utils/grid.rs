@@ -0,0 +1,137 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A position in a grid, as `(x, y)`: column, then row.
+pub type Position = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const ALL_DIRECTIONS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// A rectangular grid, stored row by row in a single `Vec`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Self { width, height, cells: vec![value; width * height] }
+    }
+
+    /// Builds a grid from its cells, row by row.
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        assert!(width > 0 && cells.len().is_multiple_of(width), "cells don't fill a whole number of rows");
+        Self { width, height: cells.len() / width, cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): Position) -> Option<&T> {
+        if x < self.width && y < self.height { Some(&self.cells[y * self.width + x]) } else { None }
+    }
+
+    pub fn get_mut(&mut self, (x, y): Position) -> Option<&mut T> {
+        if x < self.width && y < self.height { Some(&mut self.cells[y * self.width + x]) } else { None }
+    }
+
+    /// The position `(dx, dy)` away from `position`, if it's inside the grid.
+    pub fn offset(&self, (x, y): Position, (dx, dy): (isize, isize)) -> Option<Position> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        if x < self.width && y < self.height { Some((x, y)) } else { None }
+    }
+
+    /// The positions above, left, right and below `position`, as far as
+    /// they're inside the grid.
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        ORTHOGONAL.iter().filter_map(move |&offset| self.offset(position, offset))
+    }
+
+    /// The positions around `position`, including diagonally, as far as
+    /// they're inside the grid.
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        ALL_DIRECTIONS.iter().filter_map(move |&offset| self.offset(position, offset))
+    }
+
+    /// Every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |index| (index % width, index / width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {} is outside a grid of width {}", x, self.width);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// A copy of the grid with `amount` extra cells of `value` on every side.
+    pub fn padded(&self, amount: usize, value: T) -> Self where T: Clone {
+        let width = self.width + amount * 2;
+        let height = self.height + amount * 2;
+        let mut cells: Vec<T> = Vec::with_capacity(width * height);
+        cells.extend(std::iter::repeat_n(value.clone(), width * amount));
+        for row in self.rows() {
+            cells.extend(std::iter::repeat_n(value.clone(), amount));
+            cells.extend_from_slice(row);
+            cells.extend(std::iter::repeat_n(value.clone(), amount));
+        }
+        cells.extend(std::iter::repeat_n(value, width * amount));
+        Self { width, height, cells }
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        self.get(position).unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", position, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", position, width, height))
+    }
+}
+
+/// Draws the grid a row per line, with each cell's own `Display` output.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
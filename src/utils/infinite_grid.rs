@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use crate::utils::audit::{self, cast};
+use crate::utils::grid::Grid;
+use crate::utils::vector::Vec2;
+
+const CHUNK_SIZE: i64 = 16;
+
+/// A grid covering the whole plane, negative co-ordinates included, in which
+/// every cell holds `background` until it's set to something else. Cells are
+/// stored in square chunks, created the first time a cell in them is set to
+/// something other than the background.
+#[derive(Clone, Debug)]
+pub struct InfiniteGrid<T> {
+    background: T,
+    chunks: HashMap<Vec2, Vec<T>>,
+}
+
+fn chunk_and_index(position: Vec2) -> (Vec2, usize) {
+    let chunk = Vec2::new(position.x.div_euclid(CHUNK_SIZE), position.y.div_euclid(CHUNK_SIZE));
+    let index = position.y.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + position.x.rem_euclid(CHUNK_SIZE);
+    (chunk, index as usize)
+}
+
+impl<T: Clone + PartialEq> InfiniteGrid<T> {
+    pub fn new(background: T) -> Self {
+        Self { background, chunks: HashMap::new() }
+    }
+
+    /// A grid holding `grid` with its top left corner at `origin`.
+    pub fn from_grid(grid: &Grid<T>, origin: Vec2, background: T) -> Self {
+        let mut infinite = Self::new(background);
+        for (x, y) in grid.positions() {
+            infinite.set(origin + Vec2::new(x as i64, y as i64), grid[(x, y)].clone());
+        }
+        infinite
+    }
+
+    pub fn background(&self) -> &T {
+        &self.background
+    }
+
+    pub fn get(&self, position: Vec2) -> &T {
+        let (chunk, index) = chunk_and_index(position);
+        self.chunks.get(&chunk).map_or(&self.background, |cells| &cells[index])
+    }
+
+    pub fn set(&mut self, position: Vec2, value: T) {
+        let (chunk, index) = chunk_and_index(position);
+        if let Some(cells) = self.chunks.get_mut(&chunk) {
+            cells[index] = value;
+        } else if value != self.background {
+            let mut cells = vec![self.background.clone(); (CHUNK_SIZE * CHUNK_SIZE) as usize];
+            cells[index] = value;
+            self.chunks.insert(chunk, cells);
+        }
+    }
+
+    /// Every cell that isn't the background, in no particular order.
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2, &T)> {
+        self.chunks.iter().flat_map(move |(chunk, cells)| {
+            cells.iter().enumerate()
+                .filter(move |(_, cell)| **cell != self.background)
+                .map(move |(index, cell)| {
+                    let offset = Vec2::new(index as i64 % CHUNK_SIZE, index as i64 / CHUNK_SIZE);
+                    (*chunk * CHUNK_SIZE + offset, cell)
+                })
+        })
+    }
+
+    /// The smallest box containing every cell that isn't the background, as
+    /// its minimum and maximum corners, or `None` if there are no such cells.
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        self.cells().fold(None, |bounds, (position, _)| match bounds {
+            None => Some((position, position)),
+            Some((min, max)) => Some((
+                Vec2::new(min.x.min(position.x), min.y.min(position.y)),
+                Vec2::new(max.x.max(position.x), max.y.max(position.y)),
+            )),
+        })
+    }
+
+    /// The cells within `bounds`, as a finite grid.
+    pub fn to_grid(&self, (min, max): (Vec2, Vec2)) -> Grid<T> {
+        let width = cast!(audit::add(audit::sub(max.x, min.x), 1) => usize);
+        let height = cast!(audit::add(audit::sub(max.y, min.y), 1) => usize);
+        let mut grid = Grid::new(width, height, self.background.clone());
+        // Copying chunk by chunk saves looking up the chunk for every cell.
+        for (chunk, cells) in &self.chunks {
+            for (index, cell) in cells.iter().enumerate() {
+                let position = *chunk * CHUNK_SIZE + Vec2::new(index as i64 % CHUNK_SIZE, index as i64 / CHUNK_SIZE);
+                if (min.x..=max.x).contains(&position.x) && (min.y..=max.y).contains(&position.y) {
+                    grid[(cast!(position.x - min.x => usize), cast!(position.y - min.y => usize))] = cell.clone();
+                }
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Positions on both sides of zero and of chunk boundaries.
+    fn positions() -> Vec<Vec2> {
+        let coordinates = [-CHUNK_SIZE - 1, -CHUNK_SIZE, -1, 0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, 3 * CHUNK_SIZE + 5];
+        coordinates.iter().flat_map(|&x| coordinates.iter().map(move |&y| Vec2::new(x, y))).collect()
+    }
+
+    #[test]
+    fn cells_match_a_map() {
+        let mut grid = InfiniteGrid::new(0);
+        let mut expected: HashMap<Vec2, i32> = HashMap::new();
+        for (index, position) in positions().into_iter().enumerate() {
+            // Some cells are set back to the background afterwards.
+            let value = index as i32 % 3;
+            grid.set(position, value);
+            expected.insert(position, value);
+        }
+        expected.retain(|_, value| *value != 0);
+
+        for position in positions() {
+            assert_eq!(*grid.get(position), expected.get(&position).copied().unwrap_or(0), "{:?}", position);
+        }
+        assert_eq!(grid.cells().map(|(position, &value)| (position, value)).collect::<HashMap<_, _>>(), expected);
+
+        let min = Vec2::new(expected.keys().map(|p| p.x).min().unwrap(), expected.keys().map(|p| p.y).min().unwrap());
+        let max = Vec2::new(expected.keys().map(|p| p.x).max().unwrap(), expected.keys().map(|p| p.y).max().unwrap());
+        assert_eq!(grid.bounds(), Some((min, max)));
+    }
+
+    #[test]
+    fn background_cells_take_no_space() {
+        let mut grid = InfiniteGrid::new(true);
+        assert_eq!(grid.bounds(), None);
+        grid.set(Vec2::new(-100, 100), true);
+        assert!(grid.chunks.is_empty());
+        assert!(*grid.get(Vec2::new(5, -5)));
+        assert_eq!(grid.bounds(), None);
+    }
+
+    #[test]
+    fn grids_round_trip() {
+        let grid = Grid::from_cells(3, (0..12).collect());
+        let origin = Vec2::new(-CHUNK_SIZE - 1, -2);
+        let infinite = InfiniteGrid::from_grid(&grid, origin, 0);
+        assert_eq!(infinite.bounds(), Some((origin, origin + Vec2::new(2, 3))));
+        assert_eq!(infinite.to_grid((origin, origin + Vec2::new(2, 3))), grid);
+
+        let wider = infinite.to_grid((origin - Vec2::new(1, 0), origin + Vec2::new(2, 3)));
+        assert_eq!(wider.width(), 4);
+        assert_eq!(wider.column(0).copied().collect::<Vec<_>>(), vec![0; 4]);
+        assert_eq!(wider.row(1), &[0, 3, 4, 5]);
+    }
+}
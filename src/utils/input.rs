@@ -0,0 +1,143 @@
+use std::ops::Range;
+use std::path::Path;
+
+use crate::utils::parse::Lines;
+
+/// The text that an input is read into. With the `mmap` feature, files are
+/// mapped into memory rather than read, which saves a copy of very large
+/// generated inputs.
+enum Buffer {
+    Owned(String),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+/// A puzzle input, held in a single buffer that its lines borrow from.
+///
+/// The lines are normalised on the way in: a byte order mark is skipped, and
+/// trailing whitespace and blank lines at the end are left out.
+pub struct Input {
+    buffer: Buffer,
+    start: usize,
+    lines: Vec<Range<usize>>,
+    changes: Vec<String>,
+}
+
+impl Input {
+    pub fn new(text: String) -> Self {
+        Self::from_buffer(Buffer::Owned(text))
+    }
+
+    /// Reads the file at `path`, which must be UTF-8.
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        #[cfg(feature = "mmap")]
+        {
+            let file = std::fs::File::open(path)?;
+            // Safety: the map is read-only, and we don't expect anything else
+            // to be editing puzzle inputs while we solve them.
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+            std::str::from_utf8(&map).map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+            Ok(Self::from_buffer(Buffer::Mapped(map)))
+        }
+        #[cfg(not(feature = "mmap"))]
+        {
+            Ok(Self::new(std::fs::read_to_string(path)?))
+        }
+    }
+
+    fn from_buffer(buffer: Buffer) -> Self {
+        let mut input = Self { buffer, start: 0, lines: Vec::new(), changes: Vec::new() };
+        input.normalise();
+        input
+    }
+
+    fn normalise(&mut self) {
+        let text = self.buffer_text();
+        let mut changes: Vec<String> = Vec::new();
+
+        let start = match text.strip_prefix('\u{feff}') {
+            Some(_) => {
+                changes.push("removed byte order mark".to_string());
+                '\u{feff}'.len_utf8()
+            },
+            None => 0,
+        };
+
+        let mut trimmed_lines = 0usize;
+        let mut lines: Vec<Range<usize>> = Vec::new();
+        let mut offset = start;
+        for raw_line in text[start..].split_inclusive('\n') {
+            let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let trimmed = line.trim_end();
+            if trimmed.len() != line.len() {
+                trimmed_lines += 1;
+            }
+            lines.push(offset..offset + trimmed.len());
+            offset += raw_line.len();
+        }
+        if trimmed_lines > 0 {
+            changes.push(format!("trimmed trailing whitespace from {} line(s)", trimmed_lines));
+        }
+
+        let mut removed_lines = 0usize;
+        while lines.last().is_some_and(Range::is_empty) {
+            lines.pop();
+            removed_lines += 1;
+        }
+        if removed_lines > 0 {
+            changes.push(format!("removed {} trailing blank line(s)", removed_lines));
+        }
+
+        self.start = start;
+        self.lines = lines;
+        self.changes = changes;
+    }
+
+    fn buffer_text(&self) -> &str {
+        match &self.buffer {
+            Buffer::Owned(text) => text,
+            // Safety: the map was checked to be valid UTF-8 when it was read.
+            #[cfg(feature = "mmap")]
+            Buffer::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+
+    /// The whole input, apart from any byte order mark. Unlike the lines,
+    /// this isn't normalised.
+    pub fn as_str(&self) -> &str {
+        &self.buffer_text()[self.start..]
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+
+    /// The number of lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.lines.get(index).map(|range| &self.buffer_text()[range.clone()])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        let text = self.buffer_text();
+        self.lines.iter().map(move |range| &text[range.clone()])
+    }
+
+    /// The lines, ready for parsing.
+    pub fn lines(&self) -> Lines<'_> {
+        Lines::new(self.buffer_text(), &self.lines)
+    }
+
+    /// Descriptions of anything normalisation had to change.
+    pub fn changes(&self) -> &[String] {
+        &self.changes
+    }
+}
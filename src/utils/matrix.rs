@@ -0,0 +1,364 @@
+use std::ops::{Index, IndexMut};
+
+use num_bigint::BigUint;
+
+/// Something matrices can hold: a number type with addition and
+/// multiplication. The identities come from an existing element, so that
+/// types such as `Modular` can carry their modulus along.
+pub trait Element: Clone {
+    /// The additive identity of `self`'s kind of number.
+    fn zero(&self) -> Self;
+    /// The multiplicative identity of `self`'s kind of number.
+    fn one(&self) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+/// Panics on overflow, like `u64` arithmetic in a debug build, but in every
+/// build: a silently wrapped answer would be worse than none.
+impl Element for u64 {
+    fn zero(&self) -> Self {
+        0
+    }
+
+    fn one(&self) -> Self {
+        1
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.checked_add(*other).expect("Matrix element overflowed")
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self.checked_mul(*other).expect("Matrix element overflowed")
+    }
+}
+
+impl Element for BigUint {
+    fn zero(&self) -> Self {
+        BigUint::from(0u32)
+    }
+
+    fn one(&self) -> Self {
+        BigUint::from(1u32)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+/// An integer modulo `modulus`, for when only the answer's remainder is
+/// wanted, such as when the full answer would have millions of digits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Modular {
+    pub value: u64,
+    pub modulus: u64,
+}
+
+impl Modular {
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "the modulus must be positive");
+        Self { value: value % modulus, modulus }
+    }
+}
+
+impl Element for Modular {
+    fn zero(&self) -> Self {
+        Self::new(0, self.modulus)
+    }
+
+    fn one(&self) -> Self {
+        Self::new(1, self.modulus)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.modulus, other.modulus);
+        // Both values are below the modulus, so one subtraction is enough.
+        let (sum, overflowed) = self.value.overflowing_add(other.value);
+        let value = if overflowed || sum >= self.modulus { sum.wrapping_sub(self.modulus) } else { sum };
+        Self { value, modulus: self.modulus }
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.modulus, other.modulus);
+        // Dividing a u128 is much slower, so only do it when the product
+        // might not fit in a u64.
+        let value = if self.modulus <= 1 << 32 {
+            self.value * other.value % self.modulus
+        } else {
+            ((self.value as u128 * other.value as u128) % self.modulus as u128) as u64
+        };
+        Self { value, modulus: self.modulus }
+    }
+}
+
+/// A rough non-negative number, `mantissa * 2^exponent`, for when only the
+/// size of an answer is wanted, such as to compare answers too big to work
+/// out exactly. Unlike an `f64`, it doesn't overflow to infinity.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Approximate {
+    /// From 1 up to 2, or 0 for zero.
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl Approximate {
+    pub fn new(value: u64) -> Self {
+        Self::normalise(value as f64, 0)
+    }
+
+    fn normalise(mantissa: f64, exponent: i64) -> Self {
+        if mantissa == 0.0 {
+            return Self { mantissa: 0.0, exponent: 0 };
+        }
+        // Move the f64's own exponent across, leaving its mantissa. This is
+        // done a lot, so it's done with bits rather than logarithms.
+        let bits = mantissa.to_bits();
+        let shift = ((bits >> 52) & 0x7ff) as i64 - 1023;
+        Self { mantissa: f64::from_bits(bits & !(0x7ff << 52) | (1023 << 52)), exponent: exponent + shift }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0.0
+    }
+
+    /// `self / other`, which is infinite or zero if it's too far from 1 for
+    /// an `f64`.
+    pub fn ratio(&self, other: &Self) -> f64 {
+        self.mantissa / other.mantissa * power_of_two(self.exponent - other.exponent)
+    }
+}
+
+/// `2^exponent`, going to infinity or zero past the range of normal `f64`s.
+fn power_of_two(exponent: i64) -> f64 {
+    match exponent {
+        1024.. => f64::INFINITY,
+        -1022..=1023 => f64::from_bits(((exponent + 1023) as u64) << 52),
+        _ => 0.0,
+    }
+}
+
+impl Element for Approximate {
+    fn zero(&self) -> Self {
+        Self::new(0)
+    }
+
+    fn one(&self) -> Self {
+        Self::new(1)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return if self.is_zero() { *other } else { *self };
+        }
+        let (big, small) = if self.exponent >= other.exponent { (self, other) } else { (other, self) };
+        Self::normalise(big.mantissa + small.mantissa * power_of_two(small.exponent - big.exponent), big.exponent)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return self.zero();
+        }
+        Self::normalise(self.mantissa * other.mantissa, self.exponent + other.exponent)
+    }
+}
+
+/// A dense matrix, stored row by row.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Matrix<T> {
+    rows: usize,
+    columns: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Element> Matrix<T> {
+    /// A matrix with every cell set to `zero`.
+    pub fn new(rows: usize, columns: usize, zero: T) -> Self {
+        Self { rows, columns, cells: vec![zero; rows * columns] }
+    }
+
+    /// The identity matrix, with ones of the same kind as `sample`.
+    pub fn identity(size: usize, sample: &T) -> Self {
+        let mut matrix = Self::new(size, size, sample.zero());
+        for i in 0..size {
+            matrix[(i, i)] = sample.one();
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        assert_eq!(self.columns, other.rows, "can't multiply a {}x{} matrix by a {}x{} one", self.rows, self.columns, other.rows, other.columns);
+        let mut product = Self { rows: self.rows, columns: other.columns, cells: Vec::with_capacity(self.rows * other.columns) };
+        if self.cells.is_empty() || other.cells.is_empty() {
+            // There's no element to make zeros from.
+            assert!(self.rows == 0 || other.columns == 0, "can't fill a {}x{} product of matrices with no cells", self.rows, other.columns);
+            return product;
+        }
+        product.cells = vec![self.cells[0].zero(); self.rows * other.columns];
+        // Working a row at a time avoids checking every index.
+        for (product_row, left_row) in product.cells.chunks_mut(other.columns).zip(self.cells.chunks(self.columns)) {
+            for (left, other_row) in left_row.iter().zip(other.cells.chunks(other.columns)) {
+                for (cell, right) in product_row.iter_mut().zip(other_row) {
+                    *cell = cell.add(&left.mul(right));
+                }
+            }
+        }
+        product
+    }
+
+    /// The matrix multiplied by itself `exponent` times, by repeated
+    /// squaring, so in a number of multiplications logarithmic in
+    /// `exponent`.
+    pub fn pow(&self, mut exponent: u64) -> Self {
+        assert_eq!(self.rows, self.columns, "only square matrices have powers");
+        if self.cells.is_empty() {
+            return self.clone();
+        }
+        let mut result = Self::identity(self.rows, &self.cells[0]);
+        let mut square = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&square);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.mul(&square);
+            }
+        }
+        result
+    }
+
+    /// The matrix multiplied by a column vector.
+    pub fn apply(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(self.columns, vector.len(), "can't multiply a {}x{} matrix by a vector of length {}", self.rows, self.columns, vector.len());
+        (0..self.rows)
+            .map(|row| (0..self.columns).fold(vector[0].zero(), |sum, column| sum.add(&self[(row, column)].mul(&vector[column]))))
+            .collect()
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, column): (usize, usize)) -> &T {
+        assert!(row < self.rows && column < self.columns, "({}, {}) is outside a {}x{} matrix", row, column, self.rows, self.columns);
+        &self.cells[row * self.columns + column]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut T {
+        assert!(row < self.rows && column < self.columns, "({}, {}) is outside a {}x{} matrix", row, column, self.rows, self.columns);
+        &mut self.cells[row * self.columns + column]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn matrix(rows: usize, columns: usize, cells: &[u64]) -> Matrix<u64> {
+        assert_eq!(cells.len(), rows * columns);
+        Matrix { rows, columns, cells: cells.to_vec() }
+    }
+
+    /// The Fibonacci numbers' matrix, whose powers hold the numbers.
+    fn fibonacci<T: Element>(element: impl Fn(u64) -> T) -> Matrix<T> {
+        Matrix { rows: 2, columns: 2, cells: vec![element(1), element(1), element(1), element(0)] }
+    }
+
+    fn fibonacci_numbers() -> impl Iterator<Item = BigUint> {
+        std::iter::successors(Some((BigUint::from(0u32), BigUint::from(1u32))), |(a, b)| Some((b.clone(), a + b))).map(|(a, _)| a)
+    }
+
+    #[test]
+    fn mul_multiplies() {
+        let left = matrix(2, 3, &[1, 2, 3, 4, 5, 6]);
+        let right = matrix(3, 2, &[7, 8, 9, 10, 11, 12]);
+        assert_eq!(left.mul(&right), matrix(2, 2, &[58, 64, 139, 154]));
+        assert_eq!(right.mul(&left), matrix(3, 3, &[39, 54, 69, 49, 68, 87, 59, 82, 105]));
+        assert_eq!(left.apply(&[1, 0, 2]), vec![7, 16]);
+        assert_eq!(Matrix::identity(2, &0u64).mul(&left), left);
+        assert_eq!(matrix(0, 2, &[]).mul(&matrix(2, 3, &[1, 2, 3, 4, 5, 6])), matrix(0, 3, &[]));
+    }
+
+    #[test]
+    #[should_panic(expected = "can't multiply a 2x3 matrix by a 2x3 one")]
+    fn mul_checks_sizes() {
+        let left = matrix(2, 3, &[1, 2, 3, 4, 5, 6]);
+        left.mul(&left);
+    }
+
+    #[test]
+    fn pow_matches_repeated_mul() {
+        let base = matrix(3, 3, &[1, 2, 0, 0, 1, 3, 1, 0, 1]);
+        assert_eq!(base.pow(0), Matrix::identity(3, &0u64));
+        assert_eq!(matrix(0, 0, &[]).pow(0), matrix(0, 0, &[]));
+        let mut expected = Matrix::identity(3, &0u64);
+        for exponent in 0..20 {
+            assert_eq!(base.pow(exponent), expected, "power {}", exponent);
+            expected = expected.mul(&base);
+        }
+    }
+
+    #[test]
+    fn pow_finds_fibonacci_numbers() {
+        let base = fibonacci(BigUint::from);
+        for (n, number) in fibonacci_numbers().enumerate().skip(1).take(300) {
+            assert_eq!(base.pow(n as u64)[(0, 1)], number, "F({})", n);
+        }
+    }
+
+    #[test]
+    fn modular_matches_exact() {
+        assert_eq!(Modular::new(17, 5), Modular::new(2, 5));
+        let max = Modular::new(u64::MAX - 1, u64::MAX);
+        assert_eq!(max.add(&max).value, u64::MAX - 2);
+        assert_eq!(max.mul(&max).value, 1);
+
+        for modulus in [1, 2, 7, 1_000_000_007, (1 << 32) + 15, u64::MAX] {
+            let base = fibonacci(|value| Modular::new(value, modulus));
+            for (n, number) in fibonacci_numbers().enumerate().skip(1).take(300) {
+                assert_eq!(BigUint::from(base.pow(n as u64)[(0, 1)].value), number % modulus, "F({}) mod {}", n, modulus);
+            }
+        }
+    }
+
+    #[test]
+    fn approximate_is_close() {
+        assert!(Approximate::new(0).is_zero());
+        assert_eq!(Approximate::new(6).ratio(&Approximate::new(3)), 2.0);
+        assert_eq!(Approximate::new(5).add(&Approximate::new(0)), Approximate::new(5));
+        assert_eq!(Approximate::new(5).mul(&Approximate::new(0)), Approximate::new(0));
+
+        let base = fibonacci(Approximate::new);
+        let numbers: Vec<BigUint> = fibonacci_numbers().take(3000).collect();
+        for n in [1, 2, 10, 93, 94, 1000, 2999] {
+            let approximate = base.pow(n as u64)[(0, 1)];
+            // The top 60 bits are plenty to compare with.
+            let shift = numbers[n].bits().saturating_sub(60);
+            let top = u64::try_from(&numbers[n] >> shift).unwrap();
+            let exact = Approximate::new(top).mul(&Approximate::normalise(1.0, shift as i64));
+            assert!((approximate.ratio(&exact) - 1.0).abs() < 1e-9, "F({}) is {:?}", n, approximate);
+        }
+        // Far beyond an f64's range.
+        let huge = base.pow(1_000_000)[(0, 1)];
+        assert!(huge.ratio(&Approximate::new(1)).is_infinite());
+        assert!((huge.ratio(&base.pow(999_999)[(0, 1)]) - 1.618033988749895).abs() < 1e-9);
+    }
+}
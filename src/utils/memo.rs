@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::utils::explain::explain;
+
+/// How well a `Memo` has been doing.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    /// How many values are cached.
+    pub size: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits, {} misses, {} cached", self.hits, self.misses, self.size)
+    }
+}
+
+/// A cache of the results of a recursive function of states of type `S`,
+/// keyed by something taken from each state. The function is written as
+/// the closure passed to `get`, which gets the memo back to recurse with.
+///
+/// Under `--explain`, the memo reports its stats when it's dropped.
+pub struct Memo<S, K, V> {
+    name: &'static str,
+    key: Box<dyn Fn(&S) -> K>,
+    cache: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<S: Clone + Eq + Hash + 'static, V: Clone> Memo<S, S, V> {
+    /// A memo keyed by the whole state.
+    pub fn new(name: &'static str) -> Self {
+        Self::with_key(name, S::clone)
+    }
+}
+
+impl<S, K: Eq + Hash, V: Clone> Memo<S, K, V> {
+    /// A memo keyed by `key`, which should only leave out parts of the state
+    /// that can't affect the result.
+    pub fn with_key(name: &'static str, key: impl Fn(&S) -> K + 'static) -> Self {
+        Self { name, key: Box::new(key), cache: HashMap::new(), hits: 0, misses: 0 }
+    }
+
+    /// The result for `state`, from the cache if a state with the same key
+    /// has been seen before, or else from `compute`, which can call `get`
+    /// again for the states it depends on.
+    pub fn get(&mut self, state: S, compute: impl FnOnce(&mut Self, S) -> V) -> V {
+        let key = (self.key)(&state);
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = compute(self, state);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats { hits: self.hits, misses: self.misses, size: self.cache.len() }
+    }
+}
+
+impl<S, K, V> Drop for Memo<S, K, V> {
+    fn drop(&mut self) {
+        explain!("memo {}: {}", self.name, Stats { hits: self.hits, misses: self.misses, size: self.cache.len() });
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::utils::grid::Grid;
+use crate::utils::vector::Vec2;
+
+/// The capital letters puzzles draw six cells high, each four wide apart
+/// from I and Y, with `#` for a lit cell.
+static SMALL_GLYPHS: [(char, [&str; 6]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// The capital letters puzzles draw ten cells high and six wide.
+static LARGE_GLYPHS: [(char, [&str; 10]); 15] = [
+    ('A', ["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', ["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', [".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', [".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', ["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', ["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', ["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', ["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', ["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', ["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', ["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', ["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', ["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+/// Glyphs wider or taller than this aren't drawn in errors: they can't be
+/// letters, and could be huge.
+const MAX_DRAWN_SIZE: usize = 10;
+
+/// A glyph that isn't one of the known letters.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnrecognisedGlyph {
+    /// Which glyph it was, counting from 0.
+    pub index: usize,
+    pub width: usize,
+    pub height: usize,
+    /// The glyph's cells, a row at a time, unless it's too big to draw.
+    pub cells: Vec<String>,
+}
+
+impl fmt::Display for UnrecognisedGlyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cells.is_empty() {
+            write!(f, "glyph {} is {}x{}, too big to be a letter I know", self.index, self.width, self.height)
+        } else {
+            write!(f, "glyph {} isn't a letter I know: {}", self.index, self.cells.join("/"))
+        }
+    }
+}
+
+/// Reads the letters drawn by a set of lit cells, separated by columns with
+/// nothing lit. Every letter is as tall as the lit cells, which picks the
+/// size of the letters, and only the cells' positions relative to each other
+/// matter.
+pub fn read_points(points: impl IntoIterator<Item = Vec2>) -> Result<String, UnrecognisedGlyph> {
+    // Only the lit cells are looked at, so a few cells spread far apart
+    // don't need a huge grid.
+    let mut columns: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    for point in points {
+        columns.entry(point.x).or_default().push(point.y);
+    }
+    let (top, bottom) = match (columns.values().flatten().min(), columns.values().flatten().max()) {
+        (Some(&top), Some(&bottom)) => (top, bottom),
+        _ => return Ok(String::new()),
+    };
+
+    let mut text = String::new();
+    let mut glyph: Vec<&[i64]> = Vec::new();
+    let mut columns = columns.iter().peekable();
+    while let Some((&x, ys)) = columns.next() {
+        glyph.push(ys);
+        if columns.peek().is_some_and(|&(&next_x, _)| next_x == x + 1) {
+            continue;
+        }
+        text.push(read_glyph(&glyph, top, bottom, text.len())?);
+        glyph.clear();
+    }
+    Ok(text)
+}
+
+/// Reads the letters drawn in a grid, as `read_points` does.
+pub fn read_grid(grid: &Grid<bool>) -> Result<String, UnrecognisedGlyph> {
+    read_points(grid.positions().filter(|&position| grid[position]).map(|(x, y)| Vec2::new(x as i64, y as i64)))
+}
+
+/// Reads one glyph, given the rows lit in each of its columns.
+fn read_glyph(columns: &[&[i64]], top: i64, bottom: i64, index: usize) -> Result<char, UnrecognisedGlyph> {
+    let width = columns.len();
+    let height = (bottom.abs_diff(top) as usize).saturating_add(1);
+    if width > MAX_DRAWN_SIZE || height > MAX_DRAWN_SIZE {
+        return Err(UnrecognisedGlyph { index, width, height, cells: Vec::new() });
+    }
+
+    let mut cells = vec![vec!['.'; width]; height];
+    for (x, ys) in columns.iter().enumerate() {
+        for &y in ys.iter() {
+            cells[(y - top) as usize][x] = '#';
+        }
+    }
+    let cells: Vec<String> = cells.into_iter().map(|row| row.into_iter().collect()).collect();
+    recognise(&cells).ok_or(UnrecognisedGlyph { index, width, height, cells })
+}
+
+fn recognise(cells: &[String]) -> Option<char> {
+    let matches = |glyph: &[&str]| glyph.len() == cells.len() && glyph.iter().zip(cells).all(|(expected, row)| expected == row);
+    match cells.len() {
+        6 => SMALL_GLYPHS.iter().find(|(_, glyph)| matches(glyph)).map(|&(letter, _)| letter),
+        10 => LARGE_GLYPHS.iter().find(|(_, glyph)| matches(glyph)).map(|&(letter, _)| letter),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    /// The lit cells of some glyph rows, with their top left corner at
+    /// `origin`.
+    fn points(rows: &[&str], origin: Vec2) -> Vec<Vec2> {
+        let mut points = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    points.push(origin + Vec2::new(x as i64, y as i64));
+                }
+            }
+        }
+        points
+    }
+
+    /// The lit cells of `glyphs` side by side, a blank column apart.
+    fn text_points<const H: usize>(glyphs: &[(char, [&str; H])], origin: Vec2) -> Vec<Vec2> {
+        let mut all = Vec::new();
+        let mut x = 0;
+        for (_, rows) in glyphs {
+            all.extend(points(rows, origin + Vec2::new(x, 0)));
+            x += rows[0].len() as i64 + 1;
+        }
+        all
+    }
+
+    /// Each glyph's rows must be the same width and it must touch all four
+    /// sides of its box, or glyphs wouldn't be split and sized correctly.
+    fn check_table<const H: usize>(glyphs: &[(char, [&str; H])]) {
+        let mut seen = HashSet::new();
+        for (letter, rows) in glyphs {
+            let width = rows[0].len();
+            assert!(rows.iter().all(|row| row.len() == width && row.chars().all(|c| c == '#' || c == '.')), "{} is malformed", letter);
+            assert!(rows[0].contains('#') && rows[H - 1].contains('#'), "{} doesn't fill its height", letter);
+            assert!(rows.iter().any(|row| row.starts_with('#')) && rows.iter().any(|row| row.ends_with('#')), "{} doesn't fill its width", letter);
+            assert!((0..width).all(|x| rows.iter().any(|row| row.as_bytes()[x] == b'#')), "{} has a blank column", letter);
+            assert!(seen.insert(*rows), "{} is the same as another letter", letter);
+        }
+    }
+
+    #[test]
+    fn small_glyphs_round_trip() {
+        check_table(&SMALL_GLYPHS);
+        for (letter, rows) in &SMALL_GLYPHS {
+            assert_eq!(read_points(points(rows, Vec2::new(3, -7))), Ok(letter.to_string()));
+        }
+        let expected: String = SMALL_GLYPHS.iter().map(|(letter, _)| letter).collect();
+        assert_eq!(read_points(text_points(&SMALL_GLYPHS, Vec2::default())), Ok(expected));
+    }
+
+    #[test]
+    fn large_glyphs_round_trip() {
+        check_table(&LARGE_GLYPHS);
+        for (letter, rows) in &LARGE_GLYPHS {
+            assert_eq!(read_points(points(rows, Vec2::new(-20, 5))), Ok(letter.to_string()));
+        }
+        let expected: String = LARGE_GLYPHS.iter().map(|(letter, _)| letter).collect();
+        assert_eq!(read_points(text_points(&LARGE_GLYPHS, Vec2::default())), Ok(expected));
+    }
+
+    #[test]
+    fn grids_are_read_like_points() {
+        let text = text_points(&SMALL_GLYPHS[..8], Vec2::new(1, 2));
+        let mut grid = Grid::new(50, 10, false);
+        for point in text {
+            grid[(point.x as usize, point.y as usize)] = true;
+        }
+        assert_eq!(read_grid(&grid), Ok("ABCEFGHI".to_string()));
+        assert_eq!(read_grid(&Grid::new(5, 5, false)), Ok(String::new()));
+    }
+
+    #[test]
+    fn unrecognised_glyphs_are_named() {
+        let square = ["####", "#..#", "#..#", "#..#", "#..#", "####"];
+        let mut all = points(&SMALL_GLYPHS[0].1, Vec2::default());
+        all.extend(points(&square, Vec2::new(5, 0)));
+        let error = read_points(all).unwrap_err();
+        assert_eq!(error, UnrecognisedGlyph { index: 1, width: 4, height: 6, cells: square.iter().map(|row| row.to_string()).collect() });
+        assert_eq!(error.to_string(), "glyph 1 isn't a letter I know: ####/#..#/#..#/#..#/#..#/####");
+
+        // A letter that's too short for either size isn't anything.
+        let error = read_points(points(&SMALL_GLYPHS[0].1[..5], Vec2::default())).unwrap_err();
+        assert_eq!((error.index, error.width, error.height), (0, 4, 5));
+
+        let error = read_points(vec![Vec2::new(0, 0), Vec2::new(0, 1_000_000)]).unwrap_err();
+        assert_eq!(error, UnrecognisedGlyph { index: 0, width: 1, height: 1_000_001, cells: Vec::new() });
+        assert_eq!(error.to_string(), "glyph 0 is 1x1000001, too big to be a letter I know");
+    }
+}
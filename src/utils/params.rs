@@ -0,0 +1,66 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Types that puzzle parameters can have. Their `Display` output has to be a
+/// valid JSON value, which rules out strings.
+pub trait ParamType: FromStr + Display + PartialOrd + Copy + Send + Sync + 'static {}
+
+impl ParamType for bool {}
+impl ParamType for usize {}
+impl ParamType for u64 {}
+
+/// A named puzzle constant, such as how many days to simulate, that can be
+/// overridden from the command line with `--param day6.part2_days=1000`.
+pub struct Param<T: ParamType> {
+    name: &'static str,
+    description: &'static str,
+    default: T,
+    minimum: T,
+    value: Mutex<Option<T>>,
+}
+
+impl<T: ParamType> Param<T> {
+    pub const fn new(name: &'static str, description: &'static str, default: T, minimum: T) -> Self {
+        Self { name, description, default, minimum, value: Mutex::new(None) }
+    }
+
+    /// The value to use: the one given on the command line, if there was
+    /// one, or the default.
+    pub fn get(&self) -> T {
+        self.value.lock().unwrap().unwrap_or(self.default)
+    }
+}
+
+/// A parameter of any type, as seen by the runner.
+pub trait Parameter: Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// Overrides the value from its command line form.
+    fn set(&self, value: &str) -> Result<(), String>;
+    /// The current value, as JSON.
+    fn to_json(&self) -> String;
+}
+
+impl<T: ParamType> Parameter for Param<T> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn set(&self, value: &str) -> Result<(), String> {
+        let value = value.parse::<T>().map_err(|_| format!("{:?} isn't a valid value for {}", value, self.name))?;
+        if value < self.minimum {
+            return Err(format!("{} must be at least {}", self.name, self.minimum));
+        }
+        *self.value.lock().unwrap() = Some(value);
+        Ok(())
+    }
+
+    fn to_json(&self) -> String {
+        self.get().to_string()
+    }
+}
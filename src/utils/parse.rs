@@ -0,0 +1,244 @@
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
+
+use crate::utils::grid::Grid;
+
+/// An input parsing failure, pointing at the (1-based) line and column of the
+/// input file where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// A run of input lines that remembers where it started, so that errors from
+/// anything parsed out of it still point at the right place in the file.
+#[derive(Clone, Copy)]
+pub struct Lines<'a> {
+    text: &'a str,
+    lines: &'a [Range<usize>],
+    first_line: usize,
+}
+
+impl<'a> Lines<'a> {
+    /// The lines found at the given byte ranges of `text`.
+    pub fn new(text: &'a str, lines: &'a [Range<usize>]) -> Self {
+        Self { text, lines, first_line: 1 }
+    }
+
+    pub fn line(&self, index: usize) -> ParseResult<Line<'a>> {
+        match self.lines.get(index) {
+            Some(range) => Ok(Line { text: &self.text[range.clone()], line: self.first_line + index }),
+            None => Err(ParseError {
+                line: self.first_line + self.lines.len(),
+                column: 1,
+                message: format!("expected at least {} line(s) here, found {}", index + 1, self.lines.len()),
+            }),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Line<'a>> + 'a {
+        let text = self.text;
+        let first_line = self.first_line;
+        self.lines.iter().enumerate().map(move |(index, range)| Line { text: &text[range.clone()], line: first_line + index })
+    }
+
+    /// Parses every line with the given function, stopping at the first error.
+    pub fn parse_each<T, F: FnMut(Line<'a>) -> ParseResult<T>>(&self, f: F) -> ParseResult<Vec<T>> {
+        self.iter().map(f).collect()
+    }
+
+    /// An error about the input as a whole, such as it describing a puzzle
+    /// with no answer, pointing just past its last line.
+    pub fn end_error(&self, message: String) -> ParseError {
+        ParseError { line: self.first_line + self.lines.len(), column: 1, message }
+    }
+
+    /// Splits the lines into the sections separated by blank lines.
+    pub fn sections(&self) -> Vec<Lines<'a>> {
+        let mut sections = Vec::new();
+        let mut start = 0usize;
+        for (index, range) in self.lines.iter().enumerate() {
+            if range.is_empty() {
+                sections.push(self.slice(start, index));
+                start = index + 1;
+            }
+        }
+        sections.push(self.slice(start, self.lines.len()));
+        sections
+    }
+
+    /// Splits the lines into exactly `count` blank-line-separated sections.
+    pub fn expect_sections(&self, count: usize) -> ParseResult<Vec<Lines<'a>>> {
+        let sections = self.sections();
+        if sections.len() == count {
+            Ok(sections)
+        } else {
+            Err(ParseError {
+                line: self.first_line,
+                column: 1,
+                message: format!("expected {} blank-line-separated section(s), found {}", count, sections.len()),
+            })
+        }
+    }
+
+    /// Parses a non-empty rectangular grid of characters, using `f` to
+    /// convert each character and rejecting any it returns `None` for.
+    pub fn char_grid<T, F: Fn(char) -> Option<T>>(&self, description: &str, f: F) -> ParseResult<Grid<T>> {
+        let first_line = self.line(0)?;
+        if first_line.text.is_empty() {
+            return Err(first_line.error(0, "expected a grid, found an empty line".to_string()));
+        }
+        let width = first_line.text.chars().count();
+        let mut cells: Vec<T> = Vec::with_capacity(width * self.lines.len());
+        for line in self.iter() {
+            let mut row_length = 0usize;
+            for (index, c) in line.text.chars().enumerate() {
+                cells.push(f(c).ok_or_else(|| line.error(index, format!("expected {}, found {:?}", description, c)))?);
+                row_length += 1;
+            }
+            if row_length != width {
+                return Err(line.error(row_length.min(width), format!("expected a row of length {}, found length {}", width, row_length)));
+            }
+        }
+        Ok(Grid::from_cells(width, cells))
+    }
+
+    /// Parses a rectangular grid of decimal digits.
+    pub fn digit_grid<T: From<u8>>(&self) -> ParseResult<Grid<T>> {
+        self.char_grid("a digit", |c| c.to_digit(10).map(|digit| T::from(digit as u8)))
+    }
+
+    fn slice(&self, start: usize, end: usize) -> Lines<'a> {
+        Lines { text: self.text, lines: &self.lines[start..end], first_line: self.first_line + start }
+    }
+}
+
+/// A single input line, remembering its line number.
+#[derive(Clone, Copy)]
+pub struct Line<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+impl<'a> Line<'a> {
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.text.as_bytes()
+    }
+
+    pub fn error(&self, index: usize, message: String) -> ParseError {
+        ParseError { line: self.line, column: index + 1, message }
+    }
+
+    /// Treats the whole line as a single token.
+    pub fn as_token(&self) -> Token<'a> {
+        Token { text: self.text, line: self.line, column: 1 }
+    }
+
+    /// The whitespace-separated tokens on the line.
+    pub fn tokens(&self) -> impl Iterator<Item = Token<'a>> + 'a {
+        let text = self.text;
+        let line = self.line;
+        text.split_whitespace().map(move |token| Token { text: token, line, column: offset_within(text, token) + 1 })
+    }
+
+    pub fn token(&self, index: usize) -> ParseResult<Token<'a>> {
+        self.tokens().nth(index).ok_or_else(|| self.error(self.text.len(), format!("expected at least {} token(s)", index + 1)))
+    }
+
+    /// Parses the line as comma-separated values, e.g. `3,4,3,1,2`.
+    pub fn csv<T: FromStr>(&self) -> ParseResult<Vec<T>> {
+        self.as_token().split(",").map(|token| token.parse()).collect()
+    }
+}
+
+/// A piece of an input line, remembering where on the line it came from.
+#[derive(Clone, Copy)]
+pub struct Token<'a> {
+    text: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Token<'a> {
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn error(&self, message: String) -> ParseError {
+        ParseError { line: self.line, column: self.column, message }
+    }
+
+    pub fn parse<T: FromStr>(&self) -> ParseResult<T> {
+        self.text.parse::<T>().map_err(|_| self.error(format!("could not parse {:?} as {}", self.text, std::any::type_name::<T>())))
+    }
+
+    /// Parses a number, rejecting it unless it's within `range`, so that
+    /// later arithmetic on it can't overflow.
+    pub fn parse_in<T: FromStr + PartialOrd + fmt::Display>(&self, range: RangeInclusive<T>) -> ParseResult<T> {
+        let value = self.parse::<T>()?;
+        if range.contains(&value) {
+            Ok(value)
+        } else {
+            Err(self.error(format!("expected a number from {} to {}, found {}", range.start(), range.end(), value)))
+        }
+    }
+
+    pub fn split(&self, separator: &'a str) -> impl Iterator<Item = Token<'a>> + 'a {
+        let Token { text, line, column } = *self;
+        text.split(separator).map(move |piece| Token { text: piece, line, column: column + offset_within(text, piece) })
+    }
+
+    pub fn trim_end_matches(&self, c: char) -> Token<'a> {
+        Token { text: self.text.trim_end_matches(c), line: self.line, column: self.column }
+    }
+
+    /// Splits the token in two at the first occurrence of `separator`.
+    pub fn split_once(&self, separator: &str) -> ParseResult<(Token<'a>, Token<'a>)> {
+        let index = self.text.find(separator).ok_or_else(|| self.error(format!("expected {:?} in {:?}", separator, self.text)))?;
+        let second_start = index + separator.len();
+        Ok((
+            Token { text: &self.text[..index], line: self.line, column: self.column },
+            Token { text: &self.text[second_start..], line: self.line, column: self.column + second_start },
+        ))
+    }
+
+    /// Parses an `a,b` pair, such as a co-ordinate.
+    pub fn pair<T: FromStr>(&self) -> ParseResult<(T, T)> {
+        let (first, second) = self.split_once(",")?;
+        Ok((first.parse()?, second.parse()?))
+    }
+
+    /// Parses a `name=value` assignment, such as `x=5`.
+    pub fn assignment<T: FromStr>(&self) -> ParseResult<(&'a str, T)> {
+        let (name, value) = self.split_once("=")?;
+        Ok((name.text, value.parse()?))
+    }
+
+    /// Parses a `name=min..max` assignment, such as `x=20..30`.
+    pub fn range_assignment<T: FromStr>(&self) -> ParseResult<(&'a str, RangeInclusive<T>)> {
+        let (name, range) = self.split_once("=")?;
+        let (start, end) = range.split_once("..")?;
+        Ok((name.text, start.parse()?..=end.parse()?))
+    }
+}
+
+fn offset_within(outer: &str, inner: &str) -> usize {
+    inner.as_ptr() as usize - outer.as_ptr() as usize
+}
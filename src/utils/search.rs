@@ -0,0 +1,205 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::utils::grid::{Grid, Position};
+
+/// A way from the start to a goal: its total cost, and every node along it
+/// from the start to the goal inclusive.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Path<N> {
+    pub cost: u64,
+    pub nodes: Vec<N>,
+}
+
+impl<N> Path<N> {
+    pub fn goal(&self) -> &N {
+        self.nodes.last().expect("Paths always contain the start")
+    }
+}
+
+/// Where a search keeps track of which nodes it has reached, by their index
+/// in the order they were reached.
+pub trait NodeIndices<N> {
+    fn get(&self, node: &N) -> Option<usize>;
+    fn insert(&mut self, node: N, index: usize);
+}
+
+impl<N: Eq + Hash> NodeIndices<N> for HashMap<N, usize> {
+    fn get(&self, node: &N) -> Option<usize> {
+        HashMap::get(self, node).copied()
+    }
+
+    fn insert(&mut self, node: N, index: usize) {
+        HashMap::insert(self, node, index);
+    }
+}
+
+/// Grid positions can be looked up directly, which is much quicker than
+/// hashing them.
+impl NodeIndices<Position> for Grid<Option<usize>> {
+    fn get(&self, &node: &Position) -> Option<usize> {
+        self[node]
+    }
+
+    fn insert(&mut self, node: Position, index: usize) {
+        self[node] = Some(index);
+    }
+}
+
+/// Every node reached so far, with the cheapest known cost of getting there
+/// and the index of the node it was reached from.
+struct Explored<N, M> {
+    nodes: Vec<(N, u64, Option<usize>)>,
+    indices: M,
+}
+
+impl<N: Clone, M: NodeIndices<N>> Explored<N, M> {
+    fn new(start: N, mut indices: M) -> Self {
+        indices.insert(start.clone(), 0);
+        Self { nodes: vec![(start, 0, None)], indices }
+    }
+
+    fn node(&self, index: usize) -> &N {
+        &self.nodes[index].0
+    }
+
+    fn cost(&self, index: usize) -> u64 {
+        self.nodes[index].1
+    }
+
+    /// Records a way to `node` through `parent` costing `cost`, returning
+    /// the node's index if that's cheaper than any way known before.
+    fn improve(&mut self, node: N, cost: u64, parent: usize) -> Option<usize> {
+        match self.indices.get(&node) {
+            Some(index) if self.nodes[index].1 <= cost => None,
+            Some(index) => {
+                self.nodes[index].1 = cost;
+                self.nodes[index].2 = Some(parent);
+                Some(index)
+            }
+            None => {
+                let index = self.nodes.len();
+                self.indices.insert(node.clone(), index);
+                self.nodes.push((node, cost, Some(parent)));
+                Some(index)
+            }
+        }
+    }
+
+    fn path(&self, mut index: usize) -> Path<N> {
+        let cost = self.cost(index);
+        let mut nodes = vec![self.node(index).clone()];
+        while let Some(parent) = self.nodes[index].2 {
+            nodes.push(self.node(parent).clone());
+            index = parent;
+        }
+        nodes.reverse();
+        Path { cost, nodes }
+    }
+}
+
+/// The cheapest path from `start` to a node satisfying `is_goal`, where
+/// `neighbours` gives the nodes one step away and the cost of each step.
+pub fn dijkstra<N, I>(start: N, neighbours: impl FnMut(&N) -> I, is_goal: impl FnMut(&N) -> bool) -> Option<Path<N>>
+    where N: Clone + Eq + Hash, I: IntoIterator<Item = (N, u64)> {
+    a_star(start, neighbours, |_| 0, is_goal)
+}
+
+/// Like `dijkstra`, keeping track of the nodes reached in `indices`, which
+/// should start empty.
+pub fn dijkstra_in<N, I, M>(indices: M, start: N, neighbours: impl FnMut(&N) -> I, is_goal: impl FnMut(&N) -> bool) -> Option<Path<N>>
+    where N: Clone, I: IntoIterator<Item = (N, u64)>, M: NodeIndices<N> {
+    a_star_in(indices, start, neighbours, |_| 0, is_goal)
+}
+
+/// Like `dijkstra`, but exploring nodes in order of their cost so far plus
+/// `heuristic`'s estimate of the cost still to go. The estimate must never be
+/// more than the real remaining cost, or the path found may not be the
+/// cheapest.
+pub fn a_star<N, I>(start: N, neighbours: impl FnMut(&N) -> I, heuristic: impl FnMut(&N) -> u64,
+                    is_goal: impl FnMut(&N) -> bool) -> Option<Path<N>>
+    where N: Clone + Eq + Hash, I: IntoIterator<Item = (N, u64)> {
+    a_star_in(HashMap::new(), start, neighbours, heuristic, is_goal)
+}
+
+/// Like `a_star`, keeping track of the nodes reached in `indices`, which
+/// should start empty.
+pub fn a_star_in<N, I, M>(indices: M, start: N, mut neighbours: impl FnMut(&N) -> I, mut heuristic: impl FnMut(&N) -> u64,
+                          mut is_goal: impl FnMut(&N) -> bool) -> Option<Path<N>>
+    where N: Clone, I: IntoIterator<Item = (N, u64)>, M: NodeIndices<N> {
+    let mut heap = BinaryHeap::new();
+    heap.push((Reverse(heuristic(&start)), Reverse(0), 0u64));
+    let mut explored = Explored::new(start, indices);
+
+    while let Some((_, Reverse(index), cost)) = heap.pop() {
+        // A cheaper way to this node was found after this one was queued.
+        if cost > explored.cost(index) {
+            continue;
+        }
+        if is_goal(explored.node(index)) {
+            return Some(explored.path(index));
+        }
+        for (neighbour, step) in neighbours(explored.node(index)) {
+            let estimate = heuristic(&neighbour);
+            if let Some(next) = explored.improve(neighbour, cost + step, index) {
+                heap.push((Reverse(cost + step + estimate), Reverse(next), cost + step));
+            }
+        }
+    }
+
+    None
+}
+
+/// The shortest path from `start` to a node satisfying `is_goal`, where every
+/// step costs 1.
+pub fn bfs<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I, mut is_goal: impl FnMut(&N) -> bool) -> Option<Path<N>>
+    where N: Clone + Eq + Hash, I: IntoIterator<Item = N> {
+    let mut queue = VecDeque::from(vec![0usize]);
+    let mut explored = Explored::new(start, HashMap::new());
+
+    while let Some(index) = queue.pop_front() {
+        if is_goal(explored.node(index)) {
+            return Some(explored.path(index));
+        }
+        let cost = explored.cost(index) + 1;
+        for neighbour in neighbours(explored.node(index)) {
+            // Nodes are reached in order of distance, so the first way to
+            // any node is as short as any other.
+            if !explored.indices.contains_key(&neighbour) {
+                queue.push_back(explored.improve(neighbour, cost, index).unwrap());
+            }
+        }
+    }
+
+    None
+}
+
+/// Like `dijkstra`, for graphs where every step costs 0 or 1, using a deque
+/// instead of a heap.
+pub fn zero_one_bfs<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I, mut is_goal: impl FnMut(&N) -> bool) -> Option<Path<N>>
+    where N: Clone + Eq + Hash, I: IntoIterator<Item = (N, u64)> {
+    let mut deque = VecDeque::from(vec![(0usize, 0u64)]);
+    let mut explored = Explored::new(start, HashMap::new());
+
+    while let Some((index, cost)) = deque.pop_front() {
+        if cost > explored.cost(index) {
+            continue;
+        }
+        if is_goal(explored.node(index)) {
+            return Some(explored.path(index));
+        }
+        for (neighbour, step) in neighbours(explored.node(index)) {
+            assert!(step <= 1, "0-1 BFS steps must cost 0 or 1, not {}", step);
+            if let Some(next) = explored.improve(neighbour, cost + step, index) {
+                if step == 0 {
+                    deque.push_front((next, cost));
+                } else {
+                    deque.push_back((next, cost + 1));
+                }
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,139 @@
+use crate::utils::input::Input;
+
+struct Signature {
+    description: &'static str,
+    matches: fn(&[&str]) -> bool,
+}
+
+// Rough checks of the shape of each day's input, so that a file saved under
+// the wrong day number can be reported before we try to solve it.
+static SIGNATURES: [Signature; 20] = [
+    Signature { description: "a list of depths", matches: |lines| all_lines(lines, is_number) },
+    Signature { description: "a list of submarine commands", matches: |lines| all_lines(lines, is_command) },
+    Signature { description: "a binary diagnostic report", matches: |lines| all_lines(lines, |line| only_chars(line, "01")) },
+    Signature { description: "a bingo game", matches: |lines| lines.len() > 2 && is_csv_numbers(lines[0]) && lines[1].is_empty() && lines[2].split_whitespace().all(is_number) },
+    Signature { description: "a list of vent lines", matches: |lines| all_lines(lines, is_vent_line) },
+    Signature { description: "a list of lanternfish timers", matches: |lines| lines.len() == 1 && is_csv_numbers(lines[0]) && lines[0].split(',').all(|timer| timer.len() == 1 && timer < "9") },
+    Signature { description: "a list of crab positions", matches: |lines| lines.len() == 1 && is_csv_numbers(lines[0]) },
+    Signature { description: "a list of seven-segment display notes", matches: |lines| all_lines(lines, |line| line.contains(" | ")) },
+    Signature { description: "a heightmap", matches: is_digit_grid },
+    Signature { description: "a list of navigation subsystem chunks", matches: |lines| all_lines(lines, |line| only_chars(line, "()[]{}<>")) },
+    Signature { description: "a grid of octopus energy levels", matches: |lines| is_digit_grid(lines) && lines.len() == 10 && lines[0].len() == 10 },
+    Signature { description: "a cave map", matches: |lines| all_lines(lines, is_cave_connection) },
+    Signature { description: "a set of dots and fold instructions", matches: |lines| !lines.is_empty() && is_pair(lines[0]) && lines.iter().any(|line| line.starts_with("fold along ")) },
+    Signature { description: "a polymer template and insertion rules", matches: |lines| lines.len() > 2 && only_chars(lines[0], "ABCDEFGHIJKLMNOPQRSTUVWXYZ") && lines[1].is_empty() && lines[2].contains(" -> ") },
+    Signature { description: "a map of risk levels", matches: is_digit_grid },
+    Signature { description: "a BITS transmission", matches: |lines| lines.len() == 1 && lines[0].chars().all(|c| c.is_ascii_hexdigit()) },
+    Signature { description: "a probe target area", matches: |lines| lines.len() == 1 && lines[0].starts_with("target area: ") },
+    Signature { description: "a list of snailfish numbers", matches: |lines| all_lines(lines, |line| line.starts_with('[') && only_chars(line, "[],0123456789")) },
+    Signature { description: "a set of scanner reports", matches: |lines| !lines.is_empty() && lines[0].starts_with("--- scanner ") },
+    Signature { description: "an image enhancement algorithm and image", matches: |lines| lines.len() > 2 && only_chars(lines[0], "#.") && lines[1].is_empty() },
+];
+
+/// An input file that doesn't look like it belongs to the day it was loaded
+/// for, but does look like it belongs to others.
+pub struct Mismatch {
+    day: usize,
+    /// Every day whose input it looks like, in order. Some inputs look like
+    /// several days', such as a grid of digits, which could be a heightmap, a
+    /// map of risk levels, or just a list of numbers.
+    likely_days: Vec<usize>,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let likely: Vec<String> = self.likely_days.iter().map(|&day| format!("day {} ({})", day, SIGNATURES[day - 1].description)).collect();
+        let likely = match likely.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+            _ => likely.join(""),
+        };
+        write!(f, "the input for day {} should be {}, but it looks like the input for {}",
+            self.day,
+            SIGNATURES[self.day - 1].description,
+            likely)
+    }
+}
+
+/// Checks that the input looks like it belongs to the given day. Inputs that
+/// don't look like any day's are let through, since they may just be unusual.
+pub fn check(day: usize, input: &Input) -> Result<(), Mismatch> {
+    let lines: Vec<&str> = input.iter().collect();
+    if (SIGNATURES[day - 1].matches)(&lines) {
+        return Ok(());
+    }
+    let likely_days: Vec<usize> = (1..=SIGNATURES.len()).filter(|&likely_day| (SIGNATURES[likely_day - 1].matches)(&lines)).collect();
+    if likely_days.is_empty() {
+        Ok(())
+    } else {
+        Err(Mismatch { day, likely_days })
+    }
+}
+
+fn all_lines(lines: &[&str], f: fn(&str) -> bool) -> bool {
+    !lines.is_empty() && lines.iter().all(|line| f(line))
+}
+
+fn only_chars(line: &str, allowed: &str) -> bool {
+    !line.is_empty() && line.chars().all(|c| allowed.contains(c))
+}
+
+fn is_number(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_csv_numbers(line: &str) -> bool {
+    line.split(',').all(is_number)
+}
+
+fn is_pair(token: &str) -> bool {
+    token.split_once(',').is_some_and(|(first, second)| is_number(first) && is_number(second))
+}
+
+fn is_command(line: &str) -> bool {
+    match line.split_once(' ') {
+        Some((direction, distance)) => ["forward", "up", "down"].contains(&direction) && is_number(distance),
+        None => false,
+    }
+}
+
+fn is_vent_line(line: &str) -> bool {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    tokens.len() == 3 && is_pair(tokens[0]) && tokens[1] == "->" && is_pair(tokens[2])
+}
+
+fn is_cave_connection(line: &str) -> bool {
+    match line.split_once('-') {
+        Some((first, second)) => is_name(first) && is_name(second),
+        None => false,
+    }
+}
+
+fn is_name(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_digit_grid(lines: &[&str]) -> bool {
+    all_lines(lines, |line| only_chars(line, "0123456789")) && lines.iter().all(|line| line.len() == lines[0].len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mismatch(day: usize, text: &str) -> Option<String> {
+        check(day, &Input::new(text.to_string())).err().map(|mismatch| mismatch.to_string())
+    }
+
+    #[test]
+    fn lists_every_likely_day() {
+        assert_eq!(mismatch(1, "199\n200\n208\n"), None);
+        assert_eq!(mismatch(1, "nonsense\n"), None);
+        assert_eq!(mismatch(1, "forward 5\ndown 5\n").as_deref(),
+            Some("the input for day 1 should be a list of depths, but it looks like the input for day 2 (a list of submarine commands)"));
+        assert_eq!(mismatch(4, "2199943210\n3987894921\n").as_deref(),
+            Some("the input for day 4 should be a bingo game, but it looks like the input for day 1 (a list of depths), day 9 (a heightmap) or day 15 (a map of risk levels)"));
+        assert_eq!(mismatch(7, "3,4,3,1,2\n"), None);
+        assert_eq!(mismatch(3, "3,4,3,1,2\n").as_deref(),
+            Some("the input for day 3 should be a binary diagnostic report, but it looks like the input for day 6 (a list of lanternfish timers) or day 7 (a list of crab positions)"));
+    }
+}
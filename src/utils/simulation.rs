@@ -0,0 +1,20 @@
+/// Runs a simulation one step at a time, reading off each part's answer once
+/// it has run for that part's number of steps. The parts can ask for their
+/// steps in either order.
+pub fn answers_after_steps<S, A>(state: &mut S, steps: (usize, usize), mut step: impl FnMut(&mut S), answer: impl Fn(&S) -> A) -> (A, A) {
+    let mut answers: (Option<A>, Option<A>) = (None, None);
+    let mut steps_taken = 0usize;
+    loop {
+        if steps_taken == steps.0 {
+            answers.0 = Some(answer(state));
+        }
+        if steps_taken == steps.1 {
+            answers.1 = Some(answer(state));
+        }
+        if let (Some(part1), Some(part2)) = answers {
+            return (part1, part2);
+        }
+        step(state);
+        steps_taken += 1;
+    }
+}
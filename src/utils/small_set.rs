@@ -0,0 +1,157 @@
+use std::iter::FromIterator;
+
+/// A set of small numbers, below `InlineSet::CAPACITY`, kept as the bits of
+/// a single integer so that it's `Copy`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct InlineSet(u128);
+
+impl InlineSet {
+    pub const CAPACITY: usize = 128;
+
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && self.0 & (1 << value) != 0
+    }
+
+    /// Adds `value`, returning whether it wasn't already there. Panics if
+    /// `value` is too big to fit.
+    pub fn insert(&mut self, value: usize) -> bool {
+        assert!(value < Self::CAPACITY, "{} doesn't fit in an InlineSet", value);
+        let inserted = !self.contains(value);
+        self.0 |= 1 << value;
+        inserted
+    }
+
+    /// Removes `value`, returning whether it was there.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let removed = self.contains(value);
+        if removed {
+            self.0 &= !(1 << value);
+        }
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The members, smallest first.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::CAPACITY).filter(move |&value| self.contains(value))
+    }
+}
+
+/// A set of small numbers, such as ids from a `Graph`. It's an `InlineSet`
+/// until something too big for one is inserted, then moves to the heap.
+///
+/// Because of the heap variant this is only `Clone`, never `Copy`, even while
+/// its members fit inline. Use an `InlineSet` directly where a `Copy` set is
+/// needed and its members are known to be below `InlineSet::CAPACITY`.
+///
+/// Each set has only one representation, shrinking back inline when its
+/// biggest members are removed, so derived equality and hashing are right.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SmallSet {
+    Inline(InlineSet),
+    /// One bit per number, in 64-bit words, with the last word never zero
+    /// and some member too big for an `InlineSet`.
+    Heap(Vec<u64>),
+}
+
+impl Default for SmallSet {
+    fn default() -> Self {
+        SmallSet::Inline(InlineSet::new())
+    }
+}
+
+impl SmallSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        match self {
+            SmallSet::Inline(set) => set.contains(value),
+            SmallSet::Heap(words) => words.get(value / 64).is_some_and(|word| word & (1 << (value % 64)) != 0),
+        }
+    }
+
+    /// Adds `value`, returning whether it wasn't already there.
+    pub fn insert(&mut self, value: usize) -> bool {
+        if let SmallSet::Inline(set) = self {
+            if value < InlineSet::CAPACITY {
+                return set.insert(value);
+            }
+            *self = SmallSet::Heap(vec![set.0 as u64, (set.0 >> 64) as u64]);
+        }
+
+        let words = match self {
+            SmallSet::Heap(words) => words,
+            SmallSet::Inline(_) => unreachable!("the set was just moved to the heap"),
+        };
+        if words.len() <= value / 64 {
+            words.resize(value / 64 + 1, 0);
+        }
+        let inserted = words[value / 64] & (1 << (value % 64)) == 0;
+        words[value / 64] |= 1 << (value % 64);
+        inserted
+    }
+
+    /// Removes `value`, returning whether it was there.
+    pub fn remove(&mut self, value: usize) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        let words = match self {
+            SmallSet::Inline(set) => return set.remove(value),
+            SmallSet::Heap(words) => words,
+        };
+
+        words[value / 64] &= !(1 << (value % 64));
+        while words.last() == Some(&0) {
+            words.pop();
+        }
+        if words.len() <= InlineSet::CAPACITY / 64 {
+            let inline = words.iter().enumerate().fold(0u128, |bits, (index, &word)| bits | (word as u128) << (64 * index));
+            *self = SmallSet::Inline(InlineSet(inline));
+        }
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            SmallSet::Inline(set) => set.len(),
+            SmallSet::Heap(words) => words.iter().map(|word| word.count_ones() as usize).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // A set on the heap always has a member.
+        matches!(self, SmallSet::Inline(set) if set.is_empty())
+    }
+
+    /// The members, smallest first.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            SmallSet::Inline(set) => Box::new(set.iter()),
+            SmallSet::Heap(words) => Box::new((0..words.len() * 64).filter(move |&value| self.contains(value))),
+        }
+    }
+}
+
+impl FromIterator<usize> for SmallSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
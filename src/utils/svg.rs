@@ -0,0 +1,117 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static OUTPUT_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// How a shape is drawn. Colours are anything SVG understands, e.g. `"red"`
+/// or `"#ff0000"`.
+#[derive(Clone, Copy)]
+pub struct Style {
+    stroke: Option<&'static str>,
+    stroke_width: f64,
+    fill: Option<&'static str>,
+    opacity: f64,
+    dashed: bool,
+}
+
+impl Style {
+    pub fn stroke(colour: &'static str, width: f64) -> Self {
+        Self { stroke: Some(colour), stroke_width: width, fill: None, opacity: 1.0, dashed: false }
+    }
+
+    pub fn fill(colour: &'static str) -> Self {
+        Self { stroke: None, stroke_width: 0.0, fill: Some(colour), opacity: 1.0, dashed: false }
+    }
+
+    pub fn opacity(self, opacity: f64) -> Self {
+        Self { opacity, ..self }
+    }
+
+    pub fn dashed(self) -> Self {
+        Self { dashed: true, ..self }
+    }
+
+    fn attributes(&self) -> String {
+        let mut attributes = format!(r#"fill="{}""#, self.fill.unwrap_or("none"));
+        if let Some(stroke) = self.stroke {
+            write!(attributes, r#" stroke="{}" stroke-width="{}""#, stroke, self.stroke_width).unwrap();
+            if self.dashed {
+                write!(attributes, r#" stroke-dasharray="{} {}""#, self.stroke_width * 4.0, self.stroke_width * 2.0).unwrap();
+            }
+        }
+        if self.opacity < 1.0 {
+            write!(attributes, r#" opacity="{}""#, self.opacity).unwrap();
+        }
+        attributes
+    }
+}
+
+/// A minimal SVG document builder. Co-ordinates are in puzzle units; the
+/// view box maps them onto the page.
+pub struct Document {
+    view_box: (f64, f64, f64, f64),
+    body: String,
+}
+
+impl Document {
+    pub fn new(min_x: f64, min_y: f64, width: f64, height: f64) -> Self {
+        Self { view_box: (min_x, min_y, width, height), body: String::new() }
+    }
+
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), style: Style) {
+        writeln!(self.body, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#, from.0, from.1, to.0, to.1, style.attributes()).unwrap();
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, style: Style) {
+        writeln!(self.body, r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#, x, y, width, height, style.attributes()).unwrap();
+    }
+
+    pub fn polyline(&mut self, points: &[(f64, f64)], style: Style) {
+        let points = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect::<Vec<_>>().join(" ");
+        writeln!(self.body, r#"<polyline points="{}" {}/>"#, points, style.attributes()).unwrap();
+    }
+
+    pub fn text(&mut self, x: f64, y: f64, size: f64, content: &str) {
+        let escaped = content.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        writeln!(self.body, r#"<text x="{}" y="{}" font-size="{}" font-family="sans-serif">{}</text>"#, x, y, size, escaped).unwrap();
+    }
+
+    /// Starts a group of elements drawn with the given SVG transform, e.g.
+    /// `translate(0,100) scale(2)`. Must be matched by `end_group`.
+    pub fn begin_group(&mut self, transform: &str) {
+        writeln!(self.body, r#"<g transform="{}">"#, transform).unwrap();
+    }
+
+    pub fn end_group(&mut self) {
+        self.body.push_str("</g>\n");
+    }
+
+    pub fn render(&self) -> String {
+        let (min_x, min_y, width, height) = self.view_box;
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"white\"/>\n{}</svg>\n",
+            min_x, min_y, width, height, min_x, min_y, width, height, self.body)
+    }
+}
+
+/// Enables SVG export, into the given directory.
+pub fn configure(dir: PathBuf) {
+    *OUTPUT_DIR.lock().unwrap() = Some(dir);
+}
+
+/// Whether SVG export is enabled. Days should check this before doing any
+/// work to build a document.
+pub fn enabled() -> bool {
+    OUTPUT_DIR.lock().unwrap().is_some()
+}
+
+/// Writes the document to `<name>.svg` in the output directory.
+pub fn save(name: &str, document: &Document) {
+    if let Some(dir) = OUTPUT_DIR.lock().unwrap().as_ref() {
+        std::fs::create_dir_all(dir).expect("Can't create SVG directory");
+        let path = dir.join(format!("{}.svg", name));
+        std::fs::write(&path, document.render()).expect("Can't write SVG file");
+        println!("Wrote {}", path.display());
+    }
+}
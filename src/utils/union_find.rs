@@ -0,0 +1,93 @@
+/// A partition of the numbers `0..len` into disjoint sets, which can be
+/// merged. Each set is named by one of its members, its root.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    /// Only meaningful for roots.
+    sizes: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// Every number starts in a set of its own.
+    pub fn new(len: usize) -> Self {
+        Self { parents: (0..len).collect(), sizes: vec![1; len], components: len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// The root of `element`'s set. Everything on the way there is pointed
+    /// straight at the root, so later searches are quicker.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = element;
+        while current != root {
+            let parent = self.parents[current];
+            self.parents[current] = root;
+            current = parent;
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning whether they were
+    /// separate. The smaller set goes under the bigger one, which keeps the
+    /// trees shallow.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        self.components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// How many numbers are in `element`'s set.
+    pub fn size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// How many sets there are.
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// The roots of every set, in increasing order.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(move |&element| self.parents[element] == element)
+    }
+
+    /// Every set's members in increasing order, the sets ordered by their
+    /// smallest members.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut indices: Vec<Option<usize>> = vec![None; self.len()];
+        let mut components: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for element in 0..self.len() {
+            let root = self.find(element);
+            let index = *indices[root].get_or_insert_with(|| {
+                components.push(Vec::with_capacity(self.sizes[root]));
+                components.len() - 1
+            });
+            components[index].push(element);
+        }
+        components
+    }
+}
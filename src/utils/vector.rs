@@ -0,0 +1,191 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A 2D integer vector, ordered by `x` and then `y`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Vec2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// A 3D integer vector, ordered by `x`, then `y`, then `z`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default)]
+pub struct Vec3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Vec2 {
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// The distance to `other` moving only along the axes.
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Each component replaced by -1, 0 or 1, giving a single step in the
+    /// vector's direction for horizontal, vertical and 45 degree vectors.
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl Vec3 {
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// The distance to `other` moving only along the axes.
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    fn to_array(self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+macro_rules! vector_ops {
+    ($vector:ident, $($component:ident),+) => {
+        impl Add for $vector {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($component: self.$component + other.$component),+ }
+            }
+        }
+
+        impl Sub for $vector {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($component: self.$component - other.$component),+ }
+            }
+        }
+
+        impl Neg for $vector {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($component: -self.$component),+ }
+            }
+        }
+
+        impl Mul<i64> for $vector {
+            type Output = Self;
+
+            fn mul(self, scale: i64) -> Self {
+                Self { $($component: self.$component * scale),+ }
+            }
+        }
+
+        impl AddAssign for $vector {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $vector {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+    };
+}
+
+vector_ops!(Vec2, x, y);
+vector_ops!(Vec3, x, y, z);
+
+impl fmt::Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Vec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+/// One of the 24 ways of turning something in 3D so that its axes still line
+/// up with the original axes, as a matrix that vectors are multiplied by.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rotation {
+    matrix: [[i64; 3]; 3],
+}
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation { matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] };
+
+    /// All 24 rotations, starting with the identity. Every axis-aligned
+    /// matrix with a determinant of 1 (so no reflections) is one of them.
+    pub fn all() -> Vec<Rotation> {
+        const PERMUTATIONS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        let mut rotations: Vec<Rotation> = Vec::with_capacity(24);
+        for permutation in PERMUTATIONS {
+            for signs in 0..8 {
+                let mut matrix = [[0; 3]; 3];
+                for (row, &column) in permutation.iter().enumerate() {
+                    matrix[row][column] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                let rotation = Rotation { matrix };
+                if rotation.determinant() == 1 {
+                    rotations.push(rotation);
+                }
+            }
+        }
+        rotations
+    }
+
+    fn determinant(&self) -> i64 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn apply(&self, vector: Vec3) -> Vec3 {
+        let v = vector.to_array();
+        let [x, y, z] = self.matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2]);
+        Vec3::new(x, y, z)
+    }
+
+    /// The rotation that does `self` and then `next`.
+    pub fn then(&self, next: &Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, cells) in matrix.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| next.matrix[row][k] * self.matrix[k][column]).sum();
+            }
+        }
+        Rotation { matrix }
+    }
+
+    /// The rotation that undoes this one. Rotation matrices are orthogonal,
+    /// so that's just the transpose.
+    pub fn inverse(&self) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (row, cells) in matrix.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                *cell = self.matrix[column][row];
+            }
+        }
+        Rotation { matrix }
+    }
+}
+
+impl Mul<Vec3> for Rotation {
+    type Output = Vec3;
+
+    fn mul(self, vector: Vec3) -> Vec3 {
+        self.apply(vector)
+    }
+}
@@ -0,0 +1,222 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::utils::grid::Grid;
+
+static DEFAULT_OUTPUT_DIR: &str = "visualizations";
+
+// Frames are scaled up by a whole number of pixels per cell until they're
+// roughly this size, so that small grids still produce a usable image.
+const MIN_IMAGE_SIZE: usize = 400;
+
+// Delay between frames of a GIF or terminal animation.
+const FRAME_DELAY_MS: u64 = 100;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+pub type Colour = [u8; 3];
+
+pub const BLACK: Colour = [0, 0, 0];
+pub const WHITE: Colour = [255, 255, 255];
+pub const RED: Colour = [220, 40, 40];
+pub const YELLOW: Colour = [255, 220, 60];
+
+/// Picks a shade of grey for `value` on a scale from 0 to `max`.
+pub fn shade(value: u64, max: u64) -> Colour {
+    let level = (value.min(max) * 200 / max.max(1)) as u8 + 30;
+    [level, level, level]
+}
+
+/// Picks one of a set of distinguishable colours for the given label.
+pub fn label_colour(label: usize) -> Colour {
+    // Step around the hue circle by the golden angle so that neighbouring
+    // labels get very different colours.
+    let hue = (label as f64 * 137.508) % 360.0;
+    let sector = hue / 60.0;
+    let x = ((1.0 - ((sector % 2.0) - 1.0).abs()) * 200.0) as u8 + 40;
+    match sector as usize {
+        0 => [240, x, 40],
+        1 => [x, 240, 40],
+        2 => [40, 240, x],
+        3 => [40, x, 240],
+        4 => [x, 40, 240],
+        _ => [240, 40, x],
+    }
+}
+
+/// A single picture of a grid, one pixel per cell.
+#[derive(Clone)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<Colour>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: Colour) -> Self {
+        Self { width, height, pixels: vec![background; width * height] }
+    }
+
+    pub fn from_grid<T, F: Fn(&T) -> Colour>(grid: &Grid<T>, colour: F) -> Self {
+        Self { width: grid.width(), height: grid.height(), pixels: grid.iter().map(colour).collect() }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Colour) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = colour;
+        }
+    }
+
+    fn scale(&self) -> usize {
+        (MIN_IMAGE_SIZE / self.width.max(self.height).max(1)).max(1)
+    }
+
+    /// Renders the frame at the given scale, centred on a canvas of the given
+    /// size, as RGB bytes.
+    fn render(&self, scale: usize, canvas_width: usize, canvas_height: usize) -> Vec<u8> {
+        let offset_x = (canvas_width - self.width * scale) / 2;
+        let offset_y = (canvas_height - self.height * scale) / 2;
+        let mut bytes = vec![0u8; canvas_width * canvas_height * 3];
+        for y in 0..self.height * scale {
+            for x in 0..self.width * scale {
+                let colour = self.pixels[(y / scale) * self.width + (x / scale)];
+                let index = ((y + offset_y) * canvas_width + (x + offset_x)) * 3;
+                bytes[index..index + 3].copy_from_slice(&colour);
+            }
+        }
+        bytes
+    }
+}
+
+#[derive(Clone)]
+pub enum Output {
+    Ppm(PathBuf),
+    Png(PathBuf),
+    Gif(PathBuf),
+    Ansi,
+}
+
+impl Output {
+    /// Parses a `--visualize` value: `ppm`, `png` or `gif`, optionally
+    /// followed by `:<directory>`, or `ansi`.
+    pub fn parse(spec: &str) -> Self {
+        let (format, dir) = spec.split_once(':').unwrap_or((spec, DEFAULT_OUTPUT_DIR));
+        let dir = PathBuf::from(dir);
+        match format {
+            "ppm" => Self::Ppm(dir),
+            "png" => Self::Png(dir),
+            "gif" => Self::Gif(dir),
+            "ansi" => Self::Ansi,
+            _ => panic!("Visualization format must be ppm, png, gif or ansi"),
+        }
+    }
+}
+
+struct Sink {
+    output: Output,
+    day: usize,
+    frames_written: usize,
+    // GIFs are written in one go at the end, once we know how big the
+    // biggest frame is.
+    buffered_frames: Vec<Frame>,
+}
+
+impl Sink {
+    fn push(&mut self, frame: Frame) {
+        match &self.output {
+            Output::Ppm(dir) | Output::Png(dir) => {
+                let day_dir = dir.join(format!("day{}", self.day));
+                std::fs::create_dir_all(&day_dir).expect("Can't create visualization directory");
+                let scale = frame.scale();
+                let (width, height) = (frame.width * scale, frame.height * scale);
+                let bytes = frame.render(scale, width, height);
+                if let Output::Ppm(_) = self.output {
+                    let path = day_dir.join(format!("frame{:04}.ppm", self.frames_written));
+                    let mut writer = BufWriter::new(File::create(path).expect("Can't create visualization file"));
+                    write!(writer, "P6\n{} {}\n255\n", width, height).and_then(|_| writer.write_all(&bytes)).expect("Can't write visualization file");
+                } else {
+                    let path = day_dir.join(format!("frame{:04}.png", self.frames_written));
+                    let writer = BufWriter::new(File::create(path).expect("Can't create visualization file"));
+                    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+                    encoder.set_color(png::ColorType::Rgb);
+                    encoder.set_depth(png::BitDepth::Eight);
+                    encoder.write_header().and_then(|mut writer| writer.write_image_data(&bytes)).expect("Can't write visualization file");
+                }
+            },
+            Output::Gif(_) => self.buffered_frames.push(frame),
+            Output::Ansi => {
+                let mut out = String::with_capacity(frame.pixels.len() * 24);
+                // Clear the screen and home the cursor, then draw each cell as
+                // two spaces in the cell's colour.
+                out.push_str("\x1b[2J\x1b[H");
+                for row in frame.pixels.chunks(frame.width.max(1)) {
+                    for [r, g, b] in row {
+                        out.push_str(&format!("\x1b[48;2;{};{};{}m  ", r, g, b));
+                    }
+                    out.push_str("\x1b[0m\n");
+                }
+                print!("{}", out);
+                std::thread::sleep(std::time::Duration::from_millis(FRAME_DELAY_MS));
+            },
+        }
+        self.frames_written += 1;
+    }
+
+    fn finish(self) {
+        if let Output::Gif(dir) = &self.output {
+            if self.buffered_frames.is_empty() {
+                return;
+            }
+            std::fs::create_dir_all(dir).expect("Can't create visualization directory");
+            let scale = self.buffered_frames.iter().map(Frame::scale).min().unwrap();
+            let width = self.buffered_frames.iter().map(|frame| frame.width).max().unwrap() * scale;
+            let height = self.buffered_frames.iter().map(|frame| frame.height).max().unwrap() * scale;
+            let file = BufWriter::new(File::create(dir.join(format!("day{}.gif", self.day))).expect("Can't create visualization file"));
+            let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &[]).expect("Can't write visualization file");
+            encoder.set_repeat(gif::Repeat::Infinite).expect("Can't write visualization file");
+            for frame in self.buffered_frames.iter() {
+                let bytes = frame.render(scale, width, height);
+                let mut gif_frame = gif::Frame::from_rgb_speed(width as u16, height as u16, &bytes, 10);
+                gif_frame.delay = (FRAME_DELAY_MS / 10) as u16;
+                encoder.write_frame(&gif_frame).expect("Can't write visualization file");
+            }
+        }
+        if self.frames_written > 0 {
+            if let Output::Ppm(dir) | Output::Png(dir) | Output::Gif(dir) = &self.output {
+                println!("Wrote {} visualization frame(s) to {}", self.frames_written, dir.display());
+            }
+        }
+    }
+}
+
+/// Whether a visualization is being recorded. Days should check this before
+/// doing any work to build a frame.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Starts recording a visualization for the given day.
+pub fn start(output: &Output, day: usize) {
+    *SINK.lock().unwrap() = Some(Sink { output: output.clone(), day, frames_written: 0, buffered_frames: Vec::new() });
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Finishes the current day's visualization, writing out anything that's
+/// still buffered.
+pub fn finish() {
+    ENABLED.store(false, Ordering::Relaxed);
+    if let Some(sink) = SINK.lock().unwrap().take() {
+        sink.finish();
+    }
+}
+
+/// Adds a frame to the current day's visualization, if there is one.
+pub fn push(frame: Frame) {
+    if let Some(sink) = SINK.lock().unwrap().as_mut() {
+        sink.push(frame);
+    }
+}